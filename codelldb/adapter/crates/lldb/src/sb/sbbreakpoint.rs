@@ -62,6 +62,11 @@ impl SBBreakpoint {
             return self->GetHitCount();
         })
     }
+    pub fn set_enabled(&self, enabled: bool) {
+        cpp!(unsafe [self as "SBBreakpoint*", enabled as "bool"] {
+            self->SetEnabled(enabled);
+        })
+    }
     pub fn add_name(&self, name: &str) -> bool {
         with_cstr(name, |name| {
             cpp!(unsafe [self as "SBBreakpoint*", name as "const char*"] -> bool as "bool" {
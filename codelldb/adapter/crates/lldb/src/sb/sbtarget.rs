@@ -66,39 +66,61 @@ impl SBTarget {
     }
     pub fn launch_redirected(
         &self,
+        stdin_path: Option<&str>,
         stdout_path: &str,
         stderr_path: &str,
         args: impl IntoIterator<Item = String>,
+        env: Option<&[String]>,
+        working_directory: Option<&str>,
     ) -> Result<SBProcess, SBError> {
         let mut error = SBError::new();
         let listener = &SBListener::new();
-        with_cstr(stdout_path, |stdout_path| {
-            with_cstr(stderr_path, |stderr_path| {
-                let cstrs: Vec<CString> =
-                    args.into_iter().map(|a| CString::new(a).unwrap()).collect();
-                let mut ptrs: Vec<*const c_char> = cstrs.iter().map(|cs| cs.as_ptr()).collect();
-                ptrs.push(ptr::null());
-                let argv = ptrs.as_ptr();
+        with_opt_cstr(stdin_path, |stdin_path| {
+            with_cstr(stdout_path, |stdout_path| {
+                with_cstr(stderr_path, |stderr_path| {
+                    let cstrs: Vec<CString> =
+                        args.into_iter().map(|a| CString::new(a).unwrap()).collect();
+                    let mut ptrs: Vec<*const c_char> = cstrs.iter().map(|cs| cs.as_ptr()).collect();
+                    ptrs.push(ptr::null());
+                    let argv = ptrs.as_ptr();
 
-                let process = cpp!(unsafe [self as "SBTarget*", listener as "SBListener*", argv as "const char**", stdout_path as "const char*", stderr_path as "const char*", mut error as "SBError"] -> SBProcess as "SBProcess" {
-                    return self->Launch(
-                        *listener,
-                        argv,
-                        nullptr, // envp
-                        nullptr, // stdin_path
-                        stdout_path,
-                        stderr_path,
-                        nullptr, // working_directory
-                        eLaunchFlagNone, // launch_flags
-                        false, // stop_at_entry
-                        error
-                    );
-                });
-                if error.is_success() {
-                    Ok(process)
-                } else {
-                    Err(error)
-                }
+                    // `KEY=VALUE` entries; null to inherit the environment
+                    let env_cstrs: Vec<CString> = env
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|e| CString::new(e.as_str()).unwrap())
+                        .collect();
+                    let mut env_ptrs: Vec<*const c_char> =
+                        env_cstrs.iter().map(|cs| cs.as_ptr()).collect();
+                    env_ptrs.push(ptr::null());
+                    let envp = if env.is_some() {
+                        env_ptrs.as_ptr()
+                    } else {
+                        ptr::null()
+                    };
+
+                    with_opt_cstr(working_directory, |working_directory| {
+                        let process = cpp!(unsafe [self as "SBTarget*", listener as "SBListener*", argv as "const char**", envp as "const char**", stdin_path as "const char*", stdout_path as "const char*", stderr_path as "const char*", working_directory as "const char*", mut error as "SBError"] -> SBProcess as "SBProcess" {
+                            return self->Launch(
+                                *listener,
+                                argv,
+                                envp,
+                                stdin_path,
+                                stdout_path,
+                                stderr_path,
+                                working_directory,
+                                eLaunchFlagNone, // launch_flags
+                                false, // stop_at_entry
+                                error
+                            );
+                        });
+                        if error.is_success() {
+                            Ok(process)
+                        } else {
+                            Err(error)
+                        }
+                    })
+                })
             })
         })
     }
@@ -1,11 +1,15 @@
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, path::Path};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Config {
     pub workspace: Workspace,
     #[serde(default)]
     pub targets: Vec<Target>,
+    #[serde(default, rename = "watch")]
+    pub watches: Vec<Watch>,
+    #[serde(default, rename = "condition")]
+    pub conditions: Vec<Condition>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -17,6 +21,12 @@ pub struct Workspace {
 pub struct Member {
     #[serde(default)]
     pub trace: Trace,
+    /// Only trace functions matching any of these globs
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Don't trace functions matching any of these globs
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -24,6 +34,52 @@ pub struct Target {
     pub name: String,
     pub target: TargetType,
     pub argv: Vec<String>,
+    /// Environment variables to set, on top of the inherited environment
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Environment variables to remove from the inherited environment
+    #[serde(default)]
+    pub env_remove: Vec<String>,
+    /// Working directory of the program, relative to the workspace root unless it is absolute
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl Target {
+    /// The working directory of the program, resolved against the workspace root
+    pub fn working_dir(&self, root_dir: &str) -> Option<String> {
+        self.cwd
+            .as_ref()
+            .map(|cwd| Path::new(root_dir).join(cwd).to_string_lossy().into_owned())
+    }
+}
+
+/// Capture these variables whenever the program reaches the line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watch {
+    /// Path of the source file relative to the workspace root
+    pub file: String,
+    pub line: u32,
+    /// Variable names or field paths, e.g. `self.len`;
+    /// `&self.len` installs a watchpoint recording every write to it instead
+    pub vars: Vec<String>,
+    /// Only record when the captured values satisfy the condition
+    #[serde(default)]
+    pub when: Option<String>,
+}
+
+/// Only record the function calls or `fire::dbg!` values satisfying the condition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Condition {
+    /// Glob matched against the functions, like `include` / `exclude`
+    #[serde(default)]
+    pub function: Option<String>,
+    /// The expression passed to `fire::dbg!`
+    #[serde(default)]
+    pub dbg: Option<String>,
+    /// e.g. `n > 1000`, `result.is_err()`, `self.items.len() == 0`;
+    /// the return value of a function is `return_value`
+    pub when: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,6 +96,7 @@ pub enum Trace {
     None,
     CallOnly,
     Full,
+    Statements,
 }
 
 impl Trace {
@@ -48,6 +105,7 @@ impl Trace {
             Trace::None => "none",
             Trace::CallOnly => "call-only",
             Trace::Full => "full",
+            Trace::Statements => "statements",
         }
     }
 }
@@ -70,14 +128,193 @@ mod test {
             sea-query = { trace = "full" }
             main-one = { trace = "call-only" } 
             main-two = { trace = "none" } 
+            main-three = { trace = "statements" }
             shared = {}
+            utils = { trace = "full", include = ["utils::*"], exclude = ["fmt", "Display"] }
         "#,
         )?;
 
         assert_eq!(config.workspace.members["sea-query"].trace, Trace::Full);
         assert_eq!(config.workspace.members["main-one"].trace, Trace::CallOnly);
         assert_eq!(config.workspace.members["main-two"].trace, Trace::None);
+        assert_eq!(
+            config.workspace.members["main-three"].trace,
+            Trace::Statements
+        );
         assert_eq!(config.workspace.members["shared"].trace, Trace::None);
+        assert!(config.workspace.members["shared"].include.is_empty());
+        assert!(config.workspace.members["shared"].exclude.is_empty());
+        assert_eq!(
+            config.workspace.members["utils"],
+            Member {
+                trace: Trace::Full,
+                include: vec!["utils::*".into()],
+                exclude: vec!["fmt".into(), "Display".into()],
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn firedbg_toml_watch_parsing() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [workspace.members]
+            quicksort = { trace = "full" }
+
+            [[watch]]
+            file = "quicksort/src/lib.rs"
+            line = 12
+            vars = ["pivot", "self.len"]
+        "#,
+        )?;
+
+        assert_eq!(
+            config.watches,
+            vec![Watch {
+                file: "quicksort/src/lib.rs".into(),
+                line: 12,
+                vars: vec!["pivot".into(), "self.len".into()],
+                when: None,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn firedbg_toml_condition_parsing() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [workspace.members]
+            quicksort = { trace = "full" }
+
+            [[watch]]
+            file = "quicksort/src/lib.rs"
+            line = 12
+            vars = ["pivot"]
+            when = "pivot > 100"
+
+            [[condition]]
+            function = "quicksort::partition"
+            when = "arr.len() > 1000"
+
+            [[condition]]
+            dbg = "result"
+            when = "result.is_err()"
+        "#,
+        )?;
+
+        assert_eq!(config.watches[0].when.as_deref(), Some("pivot > 100"));
+        assert_eq!(
+            config.conditions,
+            vec![
+                Condition {
+                    function: Some("quicksort::partition".into()),
+                    dbg: None,
+                    when: "arr.len() > 1000".into(),
+                },
+                Condition {
+                    function: None,
+                    dbg: Some("result".into()),
+                    when: "result.is_err()".into(),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn firedbg_toml_target_parsing() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [workspace.members]
+            quicksort = { trace = "full" }
+
+            [[targets]]
+            name = "small"
+            target = { type = "binary", name = "quicksort" }
+            argv = ["10"]
+
+            [[targets]]
+            name = "prod"
+            target = { type = "binary", name = "quicksort" }
+            argv = ["1000"]
+            env = { RUST_LOG = "debug" }
+            env_remove = ["HOME"]
+            cwd = "data"
+        "#,
+        )?;
+
+        assert_eq!(
+            config.targets,
+            vec![
+                Target {
+                    name: "small".into(),
+                    target: TargetType::Binary {
+                        name: "quicksort".into()
+                    },
+                    argv: vec!["10".into()],
+                    env: BTreeMap::new(),
+                    env_remove: vec![],
+                    cwd: None,
+                },
+                Target {
+                    name: "prod".into(),
+                    target: TargetType::Binary {
+                        name: "quicksort".into()
+                    },
+                    argv: vec!["1000".into()],
+                    env: [("RUST_LOG".to_owned(), "debug".to_owned())].into(),
+                    env_remove: vec!["HOME".into()],
+                    cwd: Some("data".into()),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn firedbg_toml_target_working_dir() -> anyhow::Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [workspace.members]
+            quicksort = { trace = "full" }
+
+            [[targets]]
+            name = "none"
+            target = { type = "binary", name = "quicksort" }
+            argv = []
+
+            [[targets]]
+            name = "relative"
+            target = { type = "binary", name = "quicksort" }
+            argv = []
+            cwd = "data/small"
+
+            [[targets]]
+            name = "absolute"
+            target = { type = "binary", name = "quicksort" }
+            argv = []
+            cwd = "/var/data"
+        "#,
+        )?;
+
+        assert_eq!(
+            config
+                .targets
+                .iter()
+                .map(|target| target.working_dir("/home/quicksort"))
+                .collect::<Vec<_>>(),
+            [
+                None,
+                Some("/home/quicksort/data/small".to_owned()),
+                Some("/var/data".to_owned()),
+            ]
+        );
 
         Ok(())
     }
@@ -20,13 +20,41 @@
 //! - `example`: Run an example with debugging enabled
 //! - `test`: Run an integrated test with debugging enabled
 //! - `unit-test`: Run a unit test with debugging enabled
+//! - `attach`: Attach to a running process of a binary target, e.g. `firedbg attach <PID>`; detach on Ctrl-C, leaving the process running
 //! - `index`: Run indexer on the latest run and save it as a `.sqlite` db file
+//! - `profile`: Export a flame graph profile of the latest run, in folded stacks or speedscope format
 //! - `list-run`: List all `firedbg` runs
 //! - `open`: Open debugger view in VS Code
 //! - `help`: Print help message or the help of the given subcommand(s)
 //!
 //! You can get the help messages by appending the `--help` flag.
 //!
+//! ### Recording Windows
+//!
+//! By default the whole program run is recorded. For long-running programs, you can record only part of it; outside of the window the function breakpoints are disabled, while the program keeps running:
+//!
+//! - `--record-after <SECS>`: Start recording after the program has been running for this many seconds
+//! - `--record-for <SECS>`: Stop recording after recording for this many seconds
+//! - `--record-on-signal`: Start / stop recording whenever the program receives `SIGUSR1`, e.g. `kill -USR1 <PID>`; the signal is not delivered to the program
+//!
+//! e.g. `firedbg run server --record-after 10 --record-for 5`.
+//!
+//! ### Standard Input
+//!
+//! If the stdin of `firedbg` is not a terminal, it is passed through to the program, so `cat input.txt | firedbg run` works.
+//! An interactive CLI reads the terminal with `--stdin -`; otherwise the terminal is never read, so `firedbg run &` is not stopped in the background.
+//! Alternatively, `--stdin <FILE>` feeds the content of a file. Either way, the input read by the program is recorded in the `output` stream, so a run can be replayed with the same input.
+//!
+//! ### Program Environment
+//!
+//! The program inherits the environment and the working directory of `firedbg`. They can be changed per run:
+//!
+//! - `--env KEY=VALUE`: Set an environment variable; can be repeated
+//! - `--env-remove KEY`: Remove an inherited environment variable; can be repeated
+//! - `--cwd <DIR>`: Run the program in this directory
+//!
+//! The environment and working directory are recorded in the `debugger_info` table, so a run can be reproduced.
+//!
 //! ### The `firedbg.toml` Config File
 //!
 //! By default FireDBG will only trace the function calls of the debugging package. If you want to trace other packages in your local workspace, you will need to create a `firedbg.toml` config file on your workspace root.
@@ -34,7 +62,47 @@
 //! ```toml
 //! [workspace.members]
 //! quicksort = { trace = "full" }
-//! # Syntax: <PACKAGE> = { trace = "<full | none>" }
+//! # Syntax: <PACKAGE> = { trace = "<full | call-only | statements | none>" }
+//! # `call-only` only traces the call tree, without capturing arguments and return values
+//! # `statements` additionally captures the local variables at every statement, to replay a function line by line
+//! utils = { trace = "full", exclude = ["utils::fmt::*", "Display"] }
+//! # `include` / `exclude` are globs matched against the module path, self type, trait name, function name
+//! # and the qualified path of each function, e.g. `utils::Cat::fmt`
+//!
+//! # Capture these variables (or field paths) whenever the program reaches the line
+//! [[watch]]
+//! file = "quicksort/src/lib.rs"
+//! line = 12
+//! vars = ["pivot", "self.len"]
+//! # Optional; only record when the condition holds
+//! when = "pivot > 100"
+//!
+//! # Record every write to `self.len` from this line on, like `fire::watch!(&self.len)`
+//! [[watch]]
+//! file = "quicksort/src/lib.rs"
+//! line = 12
+//! vars = ["&self.len"]
+//!
+//! # Only record the calls to functions matching the glob when the condition holds
+//! [[condition]]
+//! function = "quicksort::partition"
+//! when = "arr.len() > 1000"
+//!
+//! # Only record `fire::dbg!(result)` when the condition holds
+//! [[condition]]
+//! dbg = "result"
+//! when = "result.is_err()"
+//!
+//! # Named run configs, e.g. `firedbg run quicksort --config quicksort_100`
+//! [[targets]]
+//! name = "quicksort_100"
+//! target = { type = "binary", name = "quicksort" }
+//! argv = ["100", "--seed", "1212"]
+//! # Optional; options given on the command line take precedence
+//! env = { RUST_LOG = "debug" }
+//! env_remove = ["RUST_BACKTRACE"]
+//! # Relative to the workspace root
+//! cwd = "quicksort"
 //! ```
 #![deny(
     missing_debug_implementations,
@@ -32,7 +32,12 @@ async fn main() -> Result<()> {
                     program_counter: 0xffff,
                     function_name: format!("fn_{fid}"),
                     function_id: SBFunctionId(0), // don't care
+                    call_only: false,
+                    recorded: true,
+                    future: None,
+                    deferred: None,
                 },
+                &Default::default(),
             )
         } else {
             let fid = state.o();
@@ -50,7 +55,12 @@ async fn main() -> Result<()> {
                     program_counter: 0xffff,
                     function_name: format!("fn_{fid}"),
                     function_id: SBFunctionId(0), // don't care
+                    call_only: false,
+                    recorded: true,
+                    future: None,
+                    deferred: None,
                 },
+                &Default::default(),
             )
         };
         if is_fn_call {
@@ -79,7 +89,12 @@ async fn main() -> Result<()> {
                 program_counter: 0xffff,
                 function_name: format!("fn_{fid}"),
                 function_id: SBFunctionId(0), // don't care
+                call_only: false,
+                recorded: true,
+                future: None,
+                deferred: None,
             },
+            &Default::default(),
         );
         if fid % 6 == 0 {
             event.write_opaque_v("return_value");
@@ -0,0 +1,621 @@
+//! A tiny expression language for conditional breakpoints.
+//!
+//! The condition is evaluated against the captured values of a breakpoint hit, e.g. the arguments of a function call:
+//!
+//! ```ignore
+//! n > 1000
+//! result.is_err()
+//! self.items.len() == 0 && !self.closed
+//! ```
+//!
+//! Supported are field access (`a.b`, `a.0`, auto deref of references and smart pointers),
+//! the methods `is_ok()`, `is_err()`, `is_some()`, `is_none()`, `len()` and `is_empty()`,
+//! comparison (`==`, `!=`, `<`, `<=`, `>`, `>=`) against literals or other values, and `!`, `&&`, `||`.
+//! The return value of a function is `return_value`; a condition referring to it is evaluated when the function returns.
+use crate::{PValue, RValue};
+use std::{cmp::Ordering, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A parsed breakpoint condition
+pub struct Condition {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp(Operand, CmpOp, Operand),
+    /// Must be evaluated to a bool
+    Operand(Operand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Literal(Literal),
+    Path { var: String, segments: Vec<Segment> },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Char(char),
+    Str(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Method(Method),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    IsOk,
+    IsErr,
+    IsSome,
+    IsNone,
+    Len,
+    IsEmpty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i128),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Op(&'static str),
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Intermediate value during evaluation
+enum Value<'a> {
+    Raw(&'a RValue),
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Char(char),
+    Str(&'a str),
+}
+
+const OPERATORS: [&str; 13] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", ".", "(", ")", "-",
+];
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected token `{token:?}` in condition `{s}`"));
+        }
+        Ok(Self { expr })
+    }
+}
+
+impl Condition {
+    /// Satisfied if either condition is satisfied
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            expr: Expr::Or(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    /// Evaluate the condition against the captured values. A condition that cannot be evaluated,
+    /// e.g. referring to a missing variable or comparing values of different types, is not satisfied.
+    pub fn eval(&self, values: &[(String, RValue)]) -> bool {
+        self.expr.eval(values).unwrap_or(false)
+    }
+
+    /// Whether the condition refers to the variable, e.g. `return_value`
+    pub fn refers_to(&self, var: &str) -> bool {
+        self.expr.refers_to(var)
+    }
+}
+
+impl Expr {
+    fn eval(&self, values: &[(String, RValue)]) -> Option<bool> {
+        match self {
+            Self::Or(l, r) => Some(l.eval(values).unwrap_or(false) || r.eval(values)?),
+            Self::And(l, r) => Some(l.eval(values)? && r.eval(values)?),
+            Self::Not(e) => Some(!e.eval(values)?),
+            Self::Cmp(l, op, r) => {
+                let ordering = compare(l.eval(values)?, r.eval(values)?)?;
+                Some(match op {
+                    CmpOp::Eq => ordering == Ordering::Equal,
+                    CmpOp::Ne => ordering != Ordering::Equal,
+                    CmpOp::Lt => ordering == Ordering::Less,
+                    CmpOp::Le => ordering != Ordering::Greater,
+                    CmpOp::Gt => ordering == Ordering::Greater,
+                    CmpOp::Ge => ordering != Ordering::Less,
+                })
+            }
+            Self::Operand(operand) => match primitive(operand.eval(values)?) {
+                Value::Bool(b) => Some(b),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Expr {
+    fn refers_to(&self, var: &str) -> bool {
+        match self {
+            Self::Or(l, r) | Self::And(l, r) => l.refers_to(var) || r.refers_to(var),
+            Self::Not(e) => e.refers_to(var),
+            Self::Cmp(l, _, r) => l.refers_to(var) || r.refers_to(var),
+            Self::Operand(operand) => operand.refers_to(var),
+        }
+    }
+}
+
+impl Operand {
+    fn refers_to(&self, name: &str) -> bool {
+        matches!(self, Self::Path { var, .. } if var == name)
+    }
+
+    fn eval<'a>(&'a self, values: &'a [(String, RValue)]) -> Option<Value<'a>> {
+        match self {
+            Self::Literal(literal) => Some(match literal {
+                Literal::Bool(b) => Value::Bool(*b),
+                Literal::Int(i) => Value::Int(*i),
+                Literal::Float(f) => Value::Float(*f),
+                Literal::Char(c) => Value::Char(*c),
+                Literal::Str(s) => Value::Str(s),
+            }),
+            Self::Path { var, segments } => {
+                let (_, value) = values.iter().find(|(name, _)| name == var)?;
+                let mut value = Value::Raw(value);
+                for segment in segments {
+                    let Value::Raw(v) = value else {
+                        return None;
+                    };
+                    let v = deref(v);
+                    value = match segment {
+                        Segment::Field(field) => Value::Raw(member(v, field)?),
+                        Segment::Method(method) => call(v, *method)?,
+                    };
+                }
+                Some(value)
+            }
+        }
+    }
+}
+
+/// Follow references and smart pointers
+fn deref(mut value: &RValue) -> &RValue {
+    loop {
+        match value {
+            RValue::Ref { value: v, .. }
+            | RValue::DynRef { value: v, .. }
+            | RValue::RefCounted { value: v, .. }
+            | RValue::DynRefCounted { value: v, .. } => value = v,
+            _ => return value,
+        }
+    }
+}
+
+fn member<'a>(value: &'a RValue, field: &str) -> Option<&'a RValue> {
+    match value {
+        RValue::Struct { fields, .. } | RValue::Union { fields, .. } => fields.get(field),
+        RValue::Tuple { items, .. } => items.get(field.parse::<usize>().ok()?),
+        RValue::Array { data, .. } => data.get(field.parse::<usize>().ok()?),
+        RValue::Option {
+            value: Some(value), ..
+        } if field == "0" => Some(value),
+        RValue::Result { value, .. } if field == "0" => Some(value),
+        _ => None,
+    }
+}
+
+fn call(value: &RValue, method: Method) -> Option<Value<'_>> {
+    Some(match (method, value) {
+        (Method::IsOk, RValue::Result { variant, .. }) => Value::Bool(variant == "Ok"),
+        (Method::IsErr, RValue::Result { variant, .. }) => Value::Bool(variant == "Err"),
+        (Method::IsSome, RValue::Option { variant, .. }) => Value::Bool(variant == "Some"),
+        (Method::IsNone, RValue::Option { variant, .. }) => Value::Bool(variant == "None"),
+        (Method::Len | Method::IsEmpty, value) => {
+            let len = match value {
+                RValue::Array { data, .. } => data.len(),
+                RValue::String { value, .. } => value.len(),
+                RValue::Bytes { value, .. } => value.len(),
+                _ => return None,
+            };
+            if method == Method::Len {
+                Value::Int(len as i128)
+            } else {
+                Value::Bool(len == 0)
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Unwrap primitives and strings from RValue
+fn primitive(value: Value) -> Value {
+    let Value::Raw(v) = value else {
+        return value;
+    };
+    match deref(v) {
+        RValue::Prim(p) => match *p {
+            PValue::bool(v) => Value::Bool(v),
+            PValue::char(v) => Value::Char(v),
+            PValue::u8(v) => Value::Int(v.into()),
+            PValue::i8(v) => Value::Int(v.into()),
+            PValue::u16(v) => Value::Int(v.into()),
+            PValue::i16(v) => Value::Int(v.into()),
+            PValue::u32(v) => Value::Int(v.into()),
+            PValue::i32(v) => Value::Int(v.into()),
+            PValue::u64(v) => Value::Int(v.into()),
+            PValue::i64(v) => Value::Int(v.into()),
+            PValue::usize(v) => Value::Int(v.into()),
+            PValue::isize(v) => Value::Int(v.into()),
+            PValue::u128(v) => i128::try_from(v).map_or(Value::Float(v as f64), Value::Int),
+            PValue::i128(v) => Value::Int(v),
+            PValue::f32(v) => Value::Float(v.into()),
+            PValue::f64(v) => Value::Float(v),
+        },
+        RValue::String { value, .. } => Value::Str(value),
+        v => Value::Raw(v),
+    }
+}
+
+fn compare(l: Value, r: Value) -> Option<Ordering> {
+    match (primitive(l), primitive(r)) {
+        (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(&r)),
+        (Value::Int(l), Value::Int(r)) => Some(l.cmp(&r)),
+        (Value::Int(l), Value::Float(r)) => (l as f64).partial_cmp(&r),
+        (Value::Float(l), Value::Int(r)) => l.partial_cmp(&(r as f64)),
+        (Value::Float(l), Value::Float(r)) => l.partial_cmp(&r),
+        (Value::Char(l), Value::Char(r)) => Some(l.cmp(&r)),
+        (Value::Str(l), Value::Str(r)) => Some(l.cmp(r)),
+        // C-style enums can be compared to the variant name, e.g. `self.state == "Closed"`
+        (Value::Raw(RValue::Enum { variant, .. }), Value::Str(r)) => Some(variant.as_str().cmp(r)),
+        (Value::Str(l), Value::Raw(RValue::Enum { variant, .. })) => Some(l.cmp(variant.as_str())),
+        _ => None,
+    }
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+        } else if c.is_ascii_digit() {
+            let mut number = String::new();
+            // A tuple index is never a float, e.g. `a.0.1`
+            let after_dot = matches!(tokens.last(), Some(Token::Op(".")));
+            while let Some(&(_, c)) = chars.peek() {
+                let is_fraction = c == '.' && !after_dot && !number.contains('.');
+                if !(c.is_ascii_digit() || c == '_' || is_fraction) {
+                    break;
+                }
+                if c != '_' {
+                    number.push(c);
+                }
+                chars.next();
+            }
+            let token = if number.contains('.') {
+                Token::Float(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Invalid number `{number}`"))?,
+                )
+            } else {
+                Token::Int(
+                    number
+                        .parse()
+                        .map_err(|_| format!("Invalid number `{number}`"))?,
+                )
+            };
+            tokens.push(token);
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => string.push('\n'),
+                        Some((_, 't')) => string.push('\t'),
+                        Some((_, c)) => string.push(c),
+                        None => return Err(format!("Unterminated literal in `{s}`")),
+                    },
+                    Some((_, q)) if q == c => break,
+                    Some((_, c)) => string.push(c),
+                    None => return Err(format!("Unterminated literal in `{s}`")),
+                }
+            }
+            if c == '"' {
+                tokens.push(Token::Str(string));
+            } else {
+                let mut iter = string.chars();
+                match (iter.next(), iter.next()) {
+                    (Some(c), None) => tokens.push(Token::Char(c)),
+                    _ => return Err(format!("Invalid char literal `'{string}'`")),
+                }
+            }
+        } else if let Some(op) = OPERATORS.iter().find(|op| s[i..].starts_with(*op)) {
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            tokens.push(Token::Op(op));
+        } else {
+            return Err(format!("Unexpected character `{c}` in `{s}`"));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(format!("Expected `{op}`, got `{:?}`", self.peek()))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.eat("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.parse_or()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        let l = self.parse_operand()?;
+        let op = match self.peek() {
+            Some(Token::Op("==")) => CmpOp::Eq,
+            Some(Token::Op("!=")) => CmpOp::Ne,
+            Some(Token::Op("<")) => CmpOp::Lt,
+            Some(Token::Op("<=")) => CmpOp::Le,
+            Some(Token::Op(">")) => CmpOp::Gt,
+            Some(Token::Op(">=")) => CmpOp::Ge,
+            _ => return Ok(Expr::Operand(l)),
+        };
+        self.pos += 1;
+        let r = self.parse_operand()?;
+        Ok(Expr::Cmp(l, op, r))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        let negative = self.eat("-");
+        let literal = match self.next() {
+            Some(Token::Int(i)) => Literal::Int(if negative { -i } else { i }),
+            Some(Token::Float(f)) => Literal::Float(if negative { -f } else { f }),
+            _ if negative => return Err("Expected number after `-`".to_owned()),
+            Some(Token::Char(c)) => Literal::Char(c),
+            Some(Token::Str(s)) => Literal::Str(s),
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Literal::Bool(true),
+                "false" => Literal::Bool(false),
+                _ => return self.parse_path(ident),
+            },
+            other => return Err(format!("Expected value, got `{other:?}`")),
+        };
+        Ok(Operand::Literal(literal))
+    }
+
+    fn parse_path(&mut self, var: String) -> Result<Operand, String> {
+        let mut segments = Vec::new();
+        while self.eat(".") {
+            let segment = match self.next() {
+                Some(Token::Int(i)) => Segment::Field(i.to_string()),
+                Some(Token::Ident(ident)) if self.eat("(") => {
+                    self.expect(")")?;
+                    Segment::Method(match ident.as_str() {
+                        "is_ok" => Method::IsOk,
+                        "is_err" => Method::IsErr,
+                        "is_some" => Method::IsSome,
+                        "is_none" => Method::IsNone,
+                        "len" => Method::Len,
+                        "is_empty" => Method::IsEmpty,
+                        _ => return Err(format!("Unsupported method `{ident}()`")),
+                    })
+                }
+                Some(Token::Ident(ident)) => Segment::Field(ident),
+                other => return Err(format!("Expected field, got `{other:?}`")),
+            };
+            segments.push(segment);
+        }
+        Ok(Operand::Path { var, segments })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{ArrayType, RefAddr, RefType, StringType};
+
+    fn values() -> Vec<(String, RValue)> {
+        let prim = |v| RValue::Prim(PValue::u64(v));
+        vec![
+            ("n".into(), RValue::Prim(PValue::i32(1234))),
+            (
+                "self".into(),
+                RValue::Ref {
+                    typename: RefType::Ref,
+                    addr: RefAddr::Redacted,
+                    value: Box::new(RValue::Struct {
+                        typename: "Queue".into(),
+                        fields: [
+                            (
+                                "items".into(),
+                                RValue::Array {
+                                    typename: ArrayType::Vec,
+                                    data: vec![prim(1), prim(2)],
+                                },
+                            ),
+                            ("closed".into(), RValue::Prim(PValue::bool(false))),
+                            (
+                                "name".into(),
+                                RValue::String {
+                                    typename: StringType::String,
+                                    value: "jobs".into(),
+                                },
+                            ),
+                            (
+                                "state".into(),
+                                RValue::Enum {
+                                    typename: "State".into(),
+                                    variant: "Open".into(),
+                                },
+                            ),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    }),
+                },
+            ),
+            (
+                "result".into(),
+                RValue::Result {
+                    typename: "core::result::Result<u64, ()>".into(),
+                    variant: "Err".into(),
+                    value: Box::new(RValue::Unit),
+                },
+            ),
+            (
+                "pair".into(),
+                RValue::Tuple {
+                    typename: "(f64, char)".into(),
+                    items: vec![
+                        RValue::Prim(PValue::f64(0.5)),
+                        RValue::Prim(PValue::char('x')),
+                    ],
+                },
+            ),
+        ]
+    }
+
+    fn eval(condition: &str) -> bool {
+        condition
+            .parse::<Condition>()
+            .expect("Valid condition")
+            .eval(&values())
+    }
+
+    #[test]
+    fn test_condition_compare() {
+        assert!(eval("n > 1000"));
+        assert!(eval("n == 1234"));
+        assert!(!eval("n < -1"));
+        assert!(eval("1000 <= n"));
+        assert!(eval("n != 1_000"));
+        assert!(eval("pair.0 < 1"));
+        assert!(eval("pair.0 == 0.5"));
+        assert!(eval("pair.1 == 'x'"));
+        assert!(eval(r#"self.name == "jobs""#));
+        assert!(eval(r#"self.state == "Open""#));
+    }
+
+    #[test]
+    fn test_condition_method() {
+        assert!(eval("result.is_err()"));
+        assert!(!eval("result.is_ok()"));
+        assert!(eval("self.items.len() == 2"));
+        assert!(!eval("self.items.is_empty()"));
+        assert!(eval("self.items.1 == 2"));
+    }
+
+    #[test]
+    fn test_condition_logic() {
+        assert!(eval("!self.closed"));
+        assert!(eval("result.is_err() && n > 1000"));
+        assert!(eval("n < 0 || !(self.items.len() == 0)"));
+        assert!(!eval("n < 0 && result.is_err()"));
+        // Unresolvable conditions are not satisfied
+        assert!(!eval("missing > 1"));
+        assert!(!eval("n == \"1234\""));
+        assert!(eval("missing > 1 || n > 1"));
+    }
+
+    #[test]
+    fn test_condition_refers_to() {
+        let refers_to = |s: &str| s.parse::<Condition>().unwrap().refers_to("return_value");
+        assert!(refers_to("return_value.is_err()"));
+        assert!(refers_to("n > 1 && !(return_value == 2)"));
+        assert!(refers_to("1 < return_value.0"));
+        assert!(!refers_to("n > 1 || self.return_value"));
+    }
+
+    #[test]
+    fn test_condition_parse_error() {
+        assert!("n >".parse::<Condition>().is_err());
+        assert!("n > 1 1".parse::<Condition>().is_err());
+        assert!("a.foo()".parse::<Condition>().is_err());
+        assert!("a @ b".parse::<Condition>().is_err());
+    }
+}
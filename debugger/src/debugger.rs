@@ -1,6 +1,8 @@
 mod basic_type;
 mod config;
+mod crash;
 mod disassemble;
+mod output;
 mod probe;
 mod return_value;
 mod timer;
@@ -8,7 +10,9 @@ mod util;
 mod value_writer;
 
 pub use config::*;
+use crash::*;
 use disassemble::*;
+pub use output::*;
 pub(crate) use probe::*;
 use return_value::*;
 use util::*;
@@ -17,9 +21,10 @@ pub use value_writer::*;
 use crate::{
     value::{read_str, RVal, Val},
     version::rustc_version,
-    ActiveFrame, AllocAction, AllocationBorrowed, Breakpoint, BreakpointType, Bytes, EventStream,
-    SourceFile, UnionType, VariableCapture, WriteErr, ALLOCATION_STREAM, BREAKPOINT_STREAM,
-    EVENT_STREAM, FILE_STREAM, INFO_STREAM,
+    ActiveFrame, AllocAction, AllocationBorrowed, Breakpoint, BreakpointType, Bytes, Clock,
+    Condition, DeferredCall, Event, EventStream, LineColumn, OutputChannel, Panic, SourceFile,
+    UnionType, VariableCapture, WriteErr, ALLOCATION_STREAM, BREAKPOINT_STREAM, EVENT_STREAM,
+    FILE_STREAM, INFO_STREAM, OUTPUT_STREAM, PANIC_STREAM,
 };
 use anyhow::{Context, Result};
 use firedbg_protocol::{
@@ -28,19 +33,32 @@ use firedbg_protocol::{
 };
 use firedbg_rust_parser::FunctionDef;
 use lldb::{
-    IsValid, ProcessState, SBAddress, SBBreakpoint, SBData, SBDebugger, SBFunctionId, SBProcess,
-    SBSymbolId, SBTarget, SBThread, SBType, SBTypeId, SBValue, StopReason, VariableOptions,
+    IsValid, ProcessState, SBAddress, SBBreakpoint, SBCommandReturnObject, SBData, SBDebugger,
+    SBFrame, SBFunctionId, SBListener, SBProcess, SBSymbolId, SBTarget, SBThread, SBType, SBTypeId,
+    SBValue, StopReason, VariableOptions, WatchpointID,
 };
 use rustc_hash::{FxHashMap, FxHashSet};
 use sea_streamer::{Producer, SeaProducer, SeaStreamerBackend, StreamKey};
 use std::{
+    collections::{BTreeMap, HashMap},
     path::Path,
-    sync::{Arc, Mutex},
-    time::SystemTime,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
 };
 
 static mut SB_TARGET: Option<SBTarget> = None;
 static mut SB_PROCESS: Option<SBProcess> = None;
+/// Set by `Debugger::detach`
+static DETACH: AtomicBool = AtomicBool::new(false);
+/// Whether the function breakpoints are enabled
+static RECORDING: AtomicBool = AtomicBool::new(true);
+/// Start / stop recording at the next stop
+static RECORDING_REQUEST: Mutex<Option<bool>> = Mutex::new(None);
+/// The debugger has finished; `SB_PROCESS` must not be touched anymore
+static FINISHED: AtomicBool = AtomicBool::new(false);
 
 lazy_static::lazy_static! {
     static ref ENUM_CACHE: Mutex<FxHashMap<SBTypeId, Option<Arc<UnionType>>>> = Mutex::new(Default::default());
@@ -58,6 +76,42 @@ pub struct DebuggerParams {
     pub breakpoints: Vec<Breakpoint>,
     /// Arguments to pass through to the executable
     pub arguments: Vec<String>,
+    /// Attach to this running process instead of launching the executable
+    pub pid: Option<u64>,
+    /// Only record a breakpoint hit if the captured values satisfy the condition; by breakpoint id
+    pub conditions: HashMap<u32, Condition>,
+    /// Only record a `fire::dbg!` value if it satisfies the condition; by the expression name
+    pub dbg_conditions: HashMap<String, Condition>,
+    /// Only record part of the program run
+    pub recording: RecordingWindow,
+    /// Where the stdin of the program comes from
+    pub stdin: StdinSource,
+    /// Environment variables to set, on top of the inherited environment
+    pub env: BTreeMap<String, String>,
+    /// Environment variables to remove from the inherited environment
+    pub env_remove: Vec<String>,
+    /// Working directory of the program; the debugger's if not set
+    pub cwd: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// When to start and stop recording. By default, the whole program run is recorded.
+/// The function breakpoints are disabled outside of the window, while the program keeps running.
+pub struct RecordingWindow {
+    /// Start recording after the program has been running for this long
+    pub after: Option<Duration>,
+    /// Stop recording after recording for this long
+    pub length: Option<Duration>,
+    /// Start / stop recording whenever the program receives `SIGUSR1`;
+    /// recording does not start until the first signal, unless `after` is set.
+    /// The signal is not delivered to the program.
+    pub on_signal: bool,
+}
+
+impl RecordingWindow {
+    fn is_whole_run(&self) -> bool {
+        self.after.is_none() && self.length.is_none() && !self.on_signal
+    }
 }
 
 #[derive(Debug)]
@@ -65,6 +119,28 @@ struct Thread {
     frame: Frame,
     active_frames: Vec<ActiveFrame>,
     allocating: Option<String>,
+    pending_alloc: Option<PendingAlloc>,
+}
+
+/// An allocator call waiting for its return address to be hit
+struct PendingAlloc {
+    action: AllocAction,
+    size: u64,
+    align: u64,
+    old_address: Option<u64>,
+    frame_id: u64,
+    type_name: String,
+}
+
+/// A value under a write watchpoint
+struct WatchedValue {
+    watchpoint_id: WatchpointID,
+    address: u64,
+    sb_type: SBType,
+    /// Content of the memory since the last write
+    bytes: Vec<u8>,
+    /// `(thread_id, frame_id)` of the frame owning the value, if it lives on the stack
+    scope: Option<(u64, u64)>,
 }
 
 #[derive(Debug)]
@@ -84,8 +160,24 @@ pub struct BpId(pub u32);
 pub const FIREDBG_SOURCE_FILE_ID: u32 = 0;
 pub const RUST_PANIC_BP_ID: BpId = BpId(1);
 pub const FIREDBG_TRACE_BP_ID: BpId = BpId(2);
-pub const EXCHANGE_MALLOC: BpId = BpId(3);
-pub const DROP_IN_PLACE: BpId = BpId(4);
+pub const FIREDBG_WATCH_BP_ID: BpId = BpId(3);
+pub const RUST_PANIC_CLEANUP_BP_ID: BpId = BpId(4);
+pub const EXCHANGE_MALLOC: BpId = BpId(5);
+pub const DROP_IN_PLACE: BpId = BpId(6);
+pub const RUST_ALLOC: BpId = BpId(7);
+pub const RUST_REALLOC: BpId = BpId(8);
+pub const RUST_DEALLOC: BpId = BpId(9);
+
+/// The `catch_unwind` machinery; the frame outside of it is where a panic is caught
+const PANIC_CATCH_FUNCTIONS: [&str; 4] = [
+    "std::panicking::try",
+    "std::panicking::catch_unwind",
+    "std::panic::catch_unwind",
+    "__rust_try",
+];
+
+/// Tag of the breakpoints set at the return addresses of `__rust_alloc` / `__rust_realloc`
+const RUST_ALLOC_RETURN: &str = "__rust_alloc";
 
 const RET: &str = {
     #[cfg(target_arch = "x86_64")]
@@ -98,6 +190,30 @@ const RET: &str = {
     }
 };
 
+/// Registers of the first 4 integer arguments
+const ARG_REGISTERS: [&str; 4] = {
+    #[cfg(target_arch = "x86_64")]
+    {
+        ["rdi", "rsi", "rdx", "rcx"]
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        ["x0", "x1", "x2", "x3"]
+    }
+};
+
+/// Register of the integer return value
+const RET_REGISTER: &str = {
+    #[cfg(target_arch = "x86_64")]
+    {
+        "rax"
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        "x0"
+    }
+};
+
 /// This value is generated by `build.rs`.
 pub const SUPPORTED_RUSTC_VERSION: &str = env!("RUSTC_VERSION");
 
@@ -108,6 +224,17 @@ struct FunctionCache {
     cache: FxHashMap<SBFunctionId, SBType>,
 }
 
+#[derive(Default)]
+/// Hit counts of a function call breakpoint
+struct FunctionHits {
+    /// Number of times the breakpoint is hit
+    hits: u64,
+    /// Number of `FunctionCall` events streamed
+    recorded: u64,
+    /// Functions (i.e. monomorphizations) of this breakpoint
+    functions: Vec<SBFunctionId>,
+}
+
 /// Info extracted after disassemble
 struct FuncAsm {
     /// byte offset
@@ -137,6 +264,45 @@ impl Debugger {
     pub fn run(params: DebuggerParams, producer: SeaProducer) {
         run(params, producer).expect("Fail to start debugger");
     }
+
+    /// Stop the target and detach from it; the target keeps running without the breakpoints.
+    /// Can be called from any thread, e.g. on Ctrl-C.
+    pub fn detach() {
+        DETACH.store(true, Ordering::SeqCst);
+        stop_process();
+    }
+
+    /// Start recording if not recording, and vice versa. Can be called from any thread.
+    pub fn toggle_recording() {
+        request_recording(!RECORDING.load(Ordering::SeqCst));
+    }
+}
+
+fn request_recording(recording: bool) {
+    *RECORDING_REQUEST.lock().expect("Not poisoned") = Some(recording);
+    // The request may arrive just before the process resumes; keep stopping it until handled
+    loop {
+        {
+            let request = RECORDING_REQUEST.lock().expect("Not poisoned");
+            if request.is_none() || FINISHED.load(Ordering::SeqCst) {
+                break;
+            }
+            stop_process();
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn stop_process() {
+    if FINISHED.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Some(sb_process) = unsafe { SB_PROCESS.as_ref() } {
+        if let Err(err) = sb_process.stop() {
+            // The target is stopped already; requests are handled before resuming
+            log::debug!("Fail to stop process: {err:?}");
+        }
+    }
 }
 
 fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
@@ -166,6 +332,7 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
         Some(file) => get_target_basename(file.file_id().path()),
         None => panic!("Not SeaProducerBackend::File"),
     };
+    let stdin_path = &format!("{target_basename}.stdin");
     let stdout_path = &format!("{target_basename}.stdout");
     let stderr_path = &format!("{target_basename}.stderr");
 
@@ -178,10 +345,35 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
     // memory address -> type name
     let mut allocation: FxHashMap<u64, String> = Default::default();
     let mut fn_cache: FunctionCache = Default::default();
+    let mut function_hits: FxHashMap<BpId, FunctionHits> = Default::default();
+    // Functions reached `MAX_HITS_PER_FUNCTION`
+    let mut truncated_functions: FxHashSet<SBFunctionId> = Default::default();
+    // `FunctionReturn` breakpoints of each function
+    let mut return_breakpoints: FxHashMap<SBFunctionId, Vec<BpId>> = Default::default();
+    // Each watched value has a breakpoint of its own
+    let mut watched_values: FxHashMap<BpId, WatchedValue> = Default::default();
+    // Function breakpoints of `async fn`, which are also hit by each poll of their futures
+    let mut async_functions: FxHashSet<BpId> = Default::default();
+    // Address of a pending future -> (the generator function, number of polls)
+    let mut future_polls: FxHashMap<u64, (SBFunctionId, u64)> = Default::default();
 
     let bp_stream = StreamKey::new(BREAKPOINT_STREAM)?;
     let event_stream = StreamKey::new(EVENT_STREAM)?;
     let alloc_stream = StreamKey::new(ALLOCATION_STREAM)?;
+    let output_stream = StreamKey::new(OUTPUT_STREAM)?;
+    let panic_stream = StreamKey::new(PANIC_STREAM)?;
+
+    // The input and output of an attached process are not redirected
+    let mut output_tails = Vec::new();
+    let mut stdin_fifo = None;
+    if params.pid.is_none() {
+        if let Some(tail) = feed_stdin(stdin_path, &params.stdin)? {
+            output_tails.push(tail);
+            stdin_fifo = Some(stdin_path.as_str());
+        }
+        output_tails.push(OutputTail::new(OutputChannel::Stdout, stdout_path));
+        output_tails.push(OutputTail::new(OutputChannel::Stderr, stderr_path));
+    }
 
     let send_breakpoint = |bp: &Breakpoint| -> Result<()> {
         producer
@@ -221,6 +413,19 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
     register_breakpoint(&mut breakpoint_addresses, &sb_bp);
     breakpoints.push(breakpoint);
 
+    let sb_bp = sb_target.breakpoint_create_by_regex("firedbg_lib::__firedbg_watch__");
+    let breakpoint = get_firedbg_watch_breakpoint();
+    send_breakpoint(&breakpoint)?;
+    register_breakpoint(&mut breakpoint_addresses, &sb_bp);
+    breakpoints.push(breakpoint);
+
+    // Called by `catch_unwind` once it has caught a panic
+    let sb_bp = sb_target.breakpoint_create_by_name("__rust_panic_cleanup");
+    let breakpoint = get_rust_panic_cleanup_breakpoint();
+    send_breakpoint(&breakpoint)?;
+    register_breakpoint(&mut breakpoint_addresses, &sb_bp);
+    breakpoints.push(breakpoint);
+
     if !*DONT_TRACE_ALLOCATION {
         let sb_bp = sb_target.breakpoint_create_by_name("alloc::alloc::exchange_malloc");
         let breakpoint = get_exchange_malloc_breakpoint();
@@ -233,8 +438,26 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
         send_breakpoint(&breakpoint)?;
         register_breakpoint(&mut breakpoint_addresses, &sb_bp);
         breakpoints.push(breakpoint);
+
+        // The allocator shims; they forward to the `#[global_allocator]` if there is one
+        for (bp_id, name) in [
+            (RUST_ALLOC, "__rust_alloc"),
+            (RUST_REALLOC, "__rust_realloc"),
+            (RUST_DEALLOC, "__rust_dealloc"),
+        ] {
+            let sb_bp = sb_target.breakpoint_create_by_name(name);
+            let breakpoint = get_allocator_breakpoint(bp_id);
+            send_breakpoint(&breakpoint)?;
+            register_breakpoint(&mut breakpoint_addresses, &sb_bp);
+            breakpoints.push(breakpoint);
+        }
     }
 
+    // Breakpoints to be disabled outside of the recording window
+    let mut recording_breakpoints = vec![FIREDBG_TRACE_BP_ID, FIREDBG_WATCH_BP_ID];
+    // Breakpoint ids are reassigned below
+    let mut bp_conditions: FxHashMap<BpId, Condition> = Default::default();
+    let mut last_function = None;
     for (i, mut bp) in params.breakpoints.into_iter().enumerate().skip(1) {
         let crate_name = format!("{}::", &params.files[bp.file_id as usize].crate_name);
         assert_eq!(i as u32, bp.id);
@@ -295,18 +518,40 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
             }
         }
         bp.id = breakpoints.len() as u32;
+        match bp.breakpoint_type {
+            BreakpointType::FunctionCall { .. } => last_function = Some(BpId(bp.id)),
+            // The future endpoint breakpoint follows the breakpoint of its function
+            BreakpointType::FutureEndpoint => async_functions.extend(last_function),
+            _ => (),
+        }
+        if let Some(condition) = params.conditions.remove(&(i as u32)) {
+            bp_conditions.insert(BpId(bp.id), condition);
+        }
         if sb_bp.num_locations() > 0 {
             send_breakpoint(&bp)?;
+            recording_breakpoints.push(BpId(bp.id));
         }
         breakpoints.push(bp);
     }
 
+    // When attaching, the calls already running have not been traced
+    let whole_run = params.recording.is_whole_run() && params.pid.is_none();
+    let recording = params.recording.after.is_none() && !params.recording.on_signal;
+    RECORDING.store(recording, Ordering::SeqCst);
+    if !recording {
+        for bp_id in recording_breakpoints.iter() {
+            set_breakpoint_enabled(sb_target, *bp_id, false);
+        }
+    }
+
     std::mem::drop(t_set_breakpoint);
 
     let mut handle_breakpoint = |breakpoint_addresses: &mut FxHashMap<u64, BpId>,
+                                 watched_values: &mut FxHashMap<BpId, WatchedValue>,
                                  sb_process: &SBProcess,
                                  sb_thread: SBThread,
-                                 bp_id: BpId|
+                                 bp_id: BpId,
+                                 target_time: Duration|
      -> Result<()> {
         let _t = process_timer.handle_breakpoint.span();
 
@@ -317,6 +562,14 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
         let bp_capture = bp_origin.capture.clone();
         let thread_id = sb_thread.thread_id();
         let mut sb_frame = sb_thread.selected_frame();
+        let clock = Clock {
+            clock: target_time.as_nanos() as u64,
+            cpu_time: if *RECORD_CPU_TIME {
+                thread_cpu_time(sb_process.process_id(), thread_id)
+            } else {
+                0
+            },
+        };
 
         // If frame #0 is inlined,
         // we find the closest non-inlined frame to parse,
@@ -334,8 +587,111 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
             frame,
             active_frames,
             allocating,
+            pending_alloc,
         } = threads.entry(thread_id).or_default();
 
+        // Attribute the output written since the last stop to the frames active in between
+        let outputs = send_output(
+            &producer,
+            &output_stream,
+            &mut output_tails,
+            thread_id,
+            owning_frame_id(active_frames).unwrap_or_default(),
+            clock.clock,
+        )?;
+
+        if bp_id == RUST_PANIC_BP_ID {
+            // The default panic hook has printed the panic message right before `rust_panic`
+            let stderr = outputs
+                .iter()
+                .filter(|output| output.channel == OutputChannel::Stderr)
+                .map(|output| output.content.as_str())
+                .last()
+                .unwrap_or_default();
+            let (message, location) = read_panic(&sb_thread, stderr);
+            let panic = Panic {
+                thread_id,
+                // A call held back by its condition is recorded if the panic unwinds it
+                frame_id: event_frame_id(active_frames).unwrap_or_default(),
+                message,
+                location,
+                backtrace: thread_backtrace(&sb_thread),
+            };
+            producer
+                .send_to(&panic_stream, serde_json::to_string(&panic)?)
+                .context("Fail to send panic")?;
+        }
+
+        if bp_id == RUST_PANIC_CLEANUP_BP_ID {
+            // The panic has been caught; the frames it unwound through will never return
+            let catcher = find_panic_catcher(&sb_thread);
+            let catch_sp = catcher
+                .as_ref()
+                .map_or_else(|| sb_frame.sp(), |sb_catcher| sb_catcher.sp());
+            let unwound_from = active_frames
+                .iter()
+                .rposition(|active_frame| active_frame.stack_pointer >= catch_sp)
+                .map_or(0, |i| i + 1);
+            for i in (unwound_from..active_frames.len()).rev() {
+                let (outer_frames, inner_frames) = active_frames.split_at_mut(i);
+                let active_frame = &mut inner_frames[0];
+                drop_watched_values(sb_target, watched_values, thread_id, active_frame.frame_id);
+                if let Some(future) = active_frame.future {
+                    // A future which panicked is dropped, and its address may be reused
+                    future_polls.remove(&future);
+                }
+                if let Some(deferred) = active_frame.deferred.take() {
+                    // There is no return value to evaluate the condition on; record the call as it is
+                    active_frame.recorded = true;
+                    function_hits
+                        .get_mut(&deferred.bp_id)
+                        .expect("Inserted")
+                        .recorded += 1;
+                    for event in std::iter::once(deferred.call).chain(deferred.events) {
+                        stream_event(&producer, &event_stream, outer_frames, event)?;
+                    }
+                }
+                if active_frame.recorded {
+                    let event =
+                        EventStream::function_unwind(bp_id, thread_id, active_frame, &clock);
+                    stream_event(&producer, &event_stream, outer_frames, event)?;
+                }
+            }
+            let unwound = active_frames.split_off(unwound_from);
+
+            let mut event = EventStream::breakpoint(
+                bp_id,
+                thread_id,
+                event_frame_id(active_frames).unwrap_or_default(),
+                Reason::Catch,
+            );
+            if let Some(sb_catcher) = catcher {
+                let fn_name = sb_catcher.function_name().unwrap_or_default();
+                event.write_string(rwriter, "fn", fn_name);
+                if let Some(line_entry) = sb_catcher.line_entry() {
+                    let location = format!(
+                        "{}:{}:{}",
+                        line_entry.file_spec().path().display(),
+                        line_entry.line(),
+                        line_entry.column()
+                    );
+                    event.write_string(rwriter, "location", &location);
+                }
+            }
+            stream_event(&producer, &event_stream, active_frames, event)?;
+
+            for active_frame in unwound {
+                release_return_breakpoints(
+                    sb_target,
+                    &threads,
+                    &truncated_functions,
+                    &mut return_breakpoints,
+                    active_frame.function_id,
+                );
+            }
+            return Ok(());
+        }
+
         log::trace!("= Active Frame (thread={thread_id}) =");
         for af in active_frames.iter() {
             log::trace!("sp = {}", af.stack_pointer);
@@ -359,7 +715,9 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
         };
 
         let is_function_return = matches!(bp_event_type, BreakpointType::FunctionReturn);
+        let is_function_call = matches!(bp_event_type, BreakpointType::FunctionCall { .. });
         let mut return_immediately = false;
+        let mut truncate = false;
         if matches!(
             bp_event_type,
             BreakpointType::Breakpoint
@@ -367,28 +725,44 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                 | BreakpointType::FutureEndpoint
         ) {
             let mut event = if matches!(bp_event_type, BreakpointType::Breakpoint) {
-                let (active_frame_id, reason) = active_frames
-                    .last()
-                    .map(|active_frame| {
-                        let reason = match bp_id {
-                            RUST_PANIC_BP_ID => {
-                                unwind_last_active_frame(active_frame);
-                                Reason::Panic
-                            }
-                            _ => Reason::Breakpoint,
-                        };
-                        (active_frame.frame_id, reason)
-                    })
-                    .unwrap_or_default();
-                EventStream::breakpoint(bp_id, thread_id, active_frame_id, reason)
+                if bp_file_id != FIREDBG_SOURCE_FILE_ID
+                    && matches!(bp_capture, VariableCapture::Locals)
+                {
+                    // A statement breakpoint; only record it inside a recorded call of the traced function.
+                    // Otherwise we are in an unsampled or truncated call, or an inlined copy in some other function.
+                    let sb_function_id = sb_frame.function().id();
+                    if !active_frames.last().map_or(false, |active_frame| {
+                        (active_frame.recorded || active_frame.deferred.is_some())
+                            && active_frame.function_id == sb_function_id
+                    }) {
+                        return Ok(());
+                    }
+                }
+                let reason = match bp_id {
+                    RUST_PANIC_BP_ID => {
+                        if let Some(active_frame) = active_frames.last() {
+                            unwind_last_active_frame(active_frame);
+                        }
+                        Reason::Panic
+                    }
+                    _ if watched_values.contains_key(&bp_id) => Reason::Watch,
+                    _ => Reason::Breakpoint,
+                };
+                // Attribute to the innermost recorded frame; an unsampled frame has no `FunctionCall`
+                let frame_id = event_frame_id(active_frames).unwrap_or_default();
+                EventStream::breakpoint(bp_id, thread_id, frame_id, reason)
             } else if matches!(bp_event_type, BreakpointType::FunctionCall { .. }) {
                 let sb_function = sb_frame.function();
-                let get_return_type = || {
-                    let ty = sb_function.type_().function_return_type();
-                    log::debug!("{}() -> {}", sb_function.name(), ty.name());
-                    ty
-                };
-                fn_cache.insert(&sb_function.id(), get_return_type);
+                // Breakpoints of `call-only` packages capture nothing
+                let call_only = matches!(bp_capture, VariableCapture::None);
+                if !call_only {
+                    let get_return_type = || {
+                        let ty = sb_function.type_().function_return_type();
+                        log::debug!("{}() -> {}", sb_function.name(), ty.name());
+                        ty
+                    };
+                    fn_cache.insert(&sb_function.id(), get_return_type);
+                }
 
                 if let Some(active_frame) = active_frames.last() {
                     // Hit the same function call breakpoint before function returning
@@ -401,8 +775,45 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                     // SP goes deeper
                     assert!(sb_frame.sp() <= active_frame.stack_pointer);
                 }
+                if MAX_HITS_PER_FUNCTION.map_or(false, |max| {
+                    function_hits
+                        .get(&bp_id)
+                        .map_or(false, |hits| hits.recorded >= max as u64)
+                }) {
+                    // A truncated function re-enabled by the recording window
+                    set_breakpoint_enabled(sb_target, bp_id, false);
+                    return Ok(());
+                }
                 frame.frame_id += 1;
 
+                // We still push unrecorded frames to keep the frame stack consistent
+                let hits = function_hits.entry(bp_id).or_default();
+                let recorded = SAMPLE.map_or(true, |sample| sample.record(hits.hits));
+                hits.hits += 1;
+                if recorded {
+                    hits.recorded += 1;
+                    truncate =
+                        MAX_HITS_PER_FUNCTION.map_or(false, |max| hits.recorded == max as u64);
+                }
+
+                // The generator of an `async fn` is resumed with `Pin<&mut Self>` as the first argument,
+                // which points to the future and is stable across polls
+                let future = if async_functions.contains(&bp_id)
+                    && sb_function.name().ends_with("::{{closure}}")
+                {
+                    read_u64(&sb_frame.find_register(ARG_REGISTERS[0])).ok()
+                } else {
+                    None
+                };
+                if let Some(future) = future {
+                    let polls = future_polls.entry(future).or_insert((sb_function.id(), 0));
+                    if polls.0 != sb_function.id() {
+                        // The address of a dropped future is reused by a future of another `async fn`
+                        *polls = (sb_function.id(), 0);
+                    }
+                    polls.1 += 1;
+                }
+
                 // Push a new frame
                 active_frames.push(ActiveFrame {
                     frame_id: frame.frame_id,
@@ -410,6 +821,10 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                     program_counter: sb_frame.pc(),
                     function_name: sb_function.name().to_owned(),
                     function_id: sb_function.id(),
+                    call_only,
+                    recorded,
+                    future,
+                    deferred: None,
                 });
 
                 // # Why do we need to disassemble the function?
@@ -424,13 +839,22 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                 // Such that when we first break into the function, some variables are not yet written,
                 // causing us capturing garbage.
                 // To mitigate, we have to guess the extent of the prologue ourselves.
+                //
+                // For `call-only` functions we still need the `ret` breakpoints,
+                // but there is no point in extending the prologue as we capture no arguments.
                 if !functions_disassembled.contains_key(&sb_frame.pc()) {
+                    function_hits
+                        .get_mut(&bp_id)
+                        .expect("Inserted")
+                        .functions
+                        .push(sb_function.id());
                     let prologue_byte_size = sb_function.prologue_byte_size() as u64;
                     let mut prologue_address =
                         sb_function.start_address().file_address() as u64 + prologue_byte_size;
                     let pc_file_address = sb_frame.pc_address().file_address() as u64;
                     // if we were in the 'designated' prologue
-                    let mut try_extend_prologue = pc_file_address == prologue_address
+                    let mut try_extend_prologue = !call_only
+                        && pc_file_address == prologue_address
                         // but it only matters if there are arguments
                         && sb_frame
                             .variables(&VariableOptions {
@@ -500,6 +924,10 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                                     ..Default::default()
                                 };
                                 send_breakpoint(&bp)?;
+                                return_breakpoints
+                                    .entry(sb_function.id())
+                                    .or_default()
+                                    .push(BpId(bp.id));
                                 breakpoints.push(bp);
                                 ret_loc += 1;
                             }
@@ -507,6 +935,13 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                     }
                     functions_disassembled.insert(sb_frame.pc(), FuncAsm { extended_prologue });
                 }
+                if !recorded {
+                    if return_immediately {
+                        active_frames.pop();
+                    }
+                    *allocating = None;
+                    return Ok(());
+                }
                 let func_info = functions_disassembled.get(&sb_frame.pc()).expect("Cached");
                 if func_info.extended_prologue > 0 {
                     log::debug!(
@@ -520,30 +955,39 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                         .with_context(|| format!("Fail to run till address: `{addr}`"))?;
                 }
 
-                EventStream::function_call(bp_id, thread_id, active_frames.last().expect("Pushed"))
+                EventStream::function_call(
+                    bp_id,
+                    thread_id,
+                    active_frames.last().expect("Pushed"),
+                    &clock,
+                )
             } else if matches!(bp_event_type, BreakpointType::FutureEndpoint) {
                 let sb_function = sb_frame.function();
                 let fn_name = sb_function.name();
-                let (active_frame_id, reason) = active_frames
-                    .last()
-                    .map(|active_frame| {
-                        (
-                            active_frame.frame_id,
-                            if fn_name.ends_with("::{{closure}}") {
-                                Reason::FutureExit
-                            } else {
-                                Reason::FutureEnter
-                            },
-                        )
-                    })
-                    .unwrap_or_default();
-                let mut event = EventStream::breakpoint(bp_id, thread_id, active_frame_id, reason);
+                let reason = if fn_name.ends_with("::{{closure}}") {
+                    Reason::FutureExit
+                } else {
+                    Reason::FutureEnter
+                };
+                let frame_id = event_frame_id(active_frames).unwrap_or_default();
+                let mut event = EventStream::breakpoint(bp_id, thread_id, frame_id, reason);
                 event.write_string(rwriter, "fn", &fn_name.replace("::{{closure}}", ""));
+                if let Some(future) = active_frames
+                    .last()
+                    .and_then(|active_frame| active_frame.future)
+                {
+                    // The future is ready; its address may be reused by another future
+                    let polls = future_polls.remove(&future).map_or(0, |(_, polls)| polls);
+                    event.write_u64(rwriter, "future", future);
+                    event.write_u64(rwriter, "poll", polls);
+                }
                 event
             } else {
                 unreachable!();
             };
 
+            // Values to be watched after this event is recorded
+            let mut watch_requests: Vec<(String, SBValue)> = Vec::new();
             let mut write_variable = |var: SBValue| event.write_sb_value(rwriter, &var);
 
             match bp_capture {
@@ -559,6 +1003,19 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                         }
                     }
                 }
+                VariableCapture::Arguments if bp_id == FIREDBG_WATCH_BP_ID => {
+                    // Handle `fire::watch!( ... )`
+                    if let Some(name_sb_val) = sb_frame.find_variable("name") {
+                        let name_bytes = read_str(&name_sb_val)?;
+                        let name = std::str::from_utf8(&name_bytes)?;
+                        // `v` is a reference; record the initial value and watch the referent
+                        if let Some(v_sb_val) = sb_frame.find_variable("v") {
+                            let v_sb_val = v_sb_val.dereference();
+                            event.write_sb_value_renamed(rwriter, name, &v_sb_val);
+                            watch_requests.push((name.to_owned(), v_sb_val));
+                        }
+                    }
+                }
                 VariableCapture::Arguments if bp_id == EXCHANGE_MALLOC => {
                     let mut frame_idx = 1;
                     while frame_idx < sb_thread.num_frames() {
@@ -633,18 +1090,168 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                     }
                     return Ok(());
                 }
-                VariableCapture::Only(only) if bp_file_id == FIREDBG_SOURCE_FILE_ID => {
-                    if only.len() == 1 && only[0] == "exchange_malloc" {
-                        let rax = sb_frame.find_register({
-                            #[cfg(target_arch = "x86_64")]
-                            {
-                                "rax"
-                            }
-                            #[cfg(target_arch = "aarch64")]
-                            {
-                                "x0"
-                            }
+                VariableCapture::Arguments
+                    if bp_file_id == FIREDBG_SOURCE_FILE_ID
+                        && matches!(bp_id, RUST_ALLOC | RUST_REALLOC | RUST_DEALLOC) =>
+                {
+                    // `__rust_alloc(size, align)`
+                    // `__rust_realloc(ptr, old_size, align, new_size)`
+                    // `__rust_dealloc(ptr, size, align)`
+                    let mut args = [0; 4];
+                    for (arg, register) in args.iter_mut().zip(ARG_REGISTERS) {
+                        *arg = read_u64(&sb_frame.find_register(register))?;
+                    }
+                    // Allocations outside of any recorded frame (e.g. by the runtime) are ignored,
+                    // unless they are the ones of `Box` / `Rc` / `Arc`
+                    let frame_id = owning_frame_id(active_frames);
+                    let (action, size, align, old_address, type_name) = match bp_id {
+                        RUST_ALLOC => (
+                            AllocAction::Alloc,
+                            args[0],
+                            args[1],
+                            None,
+                            allocating.take(),
+                        ),
+                        RUST_REALLOC => (
+                            AllocAction::Realloc,
+                            args[3],
+                            args[2],
+                            Some(args[0]),
+                            allocation.remove(&args[0]),
+                        ),
+                        _ => (
+                            AllocAction::Dealloc,
+                            args[1],
+                            args[2],
+                            None,
+                            allocation.remove(&args[0]),
+                        ),
+                    };
+                    if frame_id.is_none() && type_name.is_none() {
+                        return Ok(());
+                    }
+                    let frame_id = frame_id.unwrap_or_default();
+                    let type_name = type_name.unwrap_or_default();
+
+                    if matches!(action, AllocAction::Dealloc) {
+                        log::debug!(
+                            "__rust_dealloc {} ({size} bytes)",
+                            crate::Addr::new(&args[0].to_ne_bytes()),
+                        );
+                        producer
+                            .send_to(
+                                &alloc_stream,
+                                serde_json::to_string(&AllocationBorrowed {
+                                    action,
+                                    address: args[0],
+                                    size,
+                                    align,
+                                    old_address,
+                                    thread_id,
+                                    frame_id,
+                                    type_name: &type_name,
+                                })?,
+                            )
+                            .context("Fail to send allocation event")?;
+                        return Ok(());
+                    }
+
+                    // The address is only known after returning; set a breakpoint at the return address
+                    if let Some(addr) = sb_thread.frames().nth(1).map(|sb_fra| sb_fra.pc()) {
+                        if !breakpoint_addresses.contains_key(&addr) {
+                            log::debug!("Set breakpoint {}", breakpoints.len());
+                            let sb_addr = SBAddress::from_load_address(addr, sb_target);
+                            let sb_bp = sb_target.breakpoint_create_by_address(&sb_addr);
+                            log::debug!("{:#?}", sb_bp);
+                            register_breakpoint(breakpoint_addresses, &sb_bp);
+                            let bp = Breakpoint {
+                                id: breakpoints.len() as u32,
+                                file_id: FIREDBG_SOURCE_FILE_ID,
+                                breakpoint_type: BreakpointType::Breakpoint,
+                                capture: VariableCapture::Only(vec![RUST_ALLOC_RETURN.to_owned()]),
+                                ..Default::default()
+                            };
+                            send_breakpoint(&bp)?;
+                            breakpoints.push(bp);
+                        }
+                        *pending_alloc = Some(PendingAlloc {
+                            action,
+                            size,
+                            align,
+                            old_address,
+                            frame_id,
+                            type_name,
                         });
+                    }
+                    return Ok(());
+                }
+                VariableCapture::Only(only) if watched_values.contains_key(&bp_id) => {
+                    // The watchpoint stops right after the write, in the frame that performed it
+                    let watched = watched_values.get_mut(&bp_id).expect("Checked");
+                    let bytes = read_process_memory(watched.address, watched.bytes.len())?;
+                    if bytes == watched.bytes {
+                        // Written with the same value
+                        return Ok(());
+                    }
+                    let old_bytes = std::mem::replace(&mut watched.bytes, bytes);
+                    if !RECORDING.load(Ordering::SeqCst) {
+                        return Ok(());
+                    }
+                    event.write_string(rwriter, "watch", &only[0]);
+                    for (name, bytes) in [("old", &old_bytes), ("new", &watched.bytes)] {
+                        let sb_data = SBData::borrow_bytes(
+                            bytes,
+                            sb_target.byte_order(),
+                            sb_target.address_byte_size(),
+                        );
+                        let sb_value =
+                            sb_target.create_value_from_data(name, &sb_data, &watched.sb_type);
+                        event.write_sb_value_renamed(rwriter, name, &sb_value);
+                    }
+                    // The writer can be inlined into the selected frame
+                    if let Some(writer) = sb_thread.frames().next() {
+                        let fn_name = writer.function_name().unwrap_or_default();
+                        event.write_string(rwriter, "fn", fn_name);
+                    }
+                }
+                VariableCapture::Only(only) if bp_file_id == FIREDBG_SOURCE_FILE_ID => {
+                    let is_alloc_return = only.len() == 1
+                        && (only[0] == "exchange_malloc" || only[0] == RUST_ALLOC_RETURN);
+                    if is_alloc_return && pending_alloc.is_some() {
+                        let rax = sb_frame.find_register(RET_REGISTER);
+                        let addr = read_u64(&rax)?;
+                        let PendingAlloc {
+                            action,
+                            size,
+                            align,
+                            old_address,
+                            frame_id,
+                            type_name,
+                        } = pending_alloc.take().unwrap();
+                        log::debug!(
+                            "{action} {} ({size} bytes) -> {type_name}",
+                            crate::Addr::new(&addr.to_ne_bytes()),
+                        );
+                        producer
+                            .send_to(
+                                &alloc_stream,
+                                serde_json::to_string(&AllocationBorrowed {
+                                    action,
+                                    address: addr,
+                                    size,
+                                    align,
+                                    old_address,
+                                    thread_id,
+                                    frame_id,
+                                    type_name: &type_name,
+                                })?,
+                            )
+                            .context("Fail to send allocation event")?;
+                        if !type_name.is_empty() {
+                            allocation.insert(addr, type_name);
+                        }
+                    } else if is_alloc_return {
+                        let rax = sb_frame.find_register(RET_REGISTER);
                         let addr = read_u64(&rax)?;
                         if allocating.is_some() {
                             log::debug!(
@@ -659,6 +1266,12 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                                     serde_json::to_string(&AllocationBorrowed {
                                         action: AllocAction::Alloc,
                                         address: addr,
+                                        size: 0,
+                                        align: 0,
+                                        old_address: None,
+                                        thread_id,
+                                        frame_id: owning_frame_id(active_frames)
+                                            .unwrap_or_default(),
                                         type_name: &ty_name,
                                     })?,
                                 )
@@ -685,6 +1298,8 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                             continue;
                         }
                         let addr = addr.unwrap().load_address(sb_target);
+                        // A future dropped before completion; its address may be reused by another future
+                        future_polls.remove(&addr);
                         if let Some(ty_name) = allocation.remove(&addr) {
                             #[cfg(debug_assertions)]
                             {
@@ -697,6 +1312,12 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                                     serde_json::to_string(&AllocationBorrowed {
                                         action: AllocAction::Drop,
                                         address: addr,
+                                        size: 0,
+                                        align: 0,
+                                        old_address: None,
+                                        thread_id,
+                                        frame_id: owning_frame_id(active_frames)
+                                            .unwrap_or_default(),
                                         type_name: &ty_name,
                                     })?,
                                 )
@@ -718,6 +1339,12 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                                         serde_json::to_string(&AllocationBorrowed {
                                             action: AllocAction::Drop,
                                             address: addr - 0x10,
+                                            size: 0,
+                                            align: 0,
+                                            old_address: None,
+                                            thread_id,
+                                            frame_id: owning_frame_id(active_frames)
+                                                .unwrap_or_default(),
                                             type_name: &ty_name,
                                         })?,
                                     )
@@ -750,21 +1377,163 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                 }
                 VariableCapture::Only(vars) => {
                     for var in vars {
-                        if let Some(var) = sb_frame.find_variable(&var) {
-                            write_variable(var);
+                        if let Some(path) = var.strip_prefix('&') {
+                            // `&path` asks for a write watchpoint instead of capturing the value
+                            if let Some(value) = find_variable_path(&sb_frame, path) {
+                                watch_requests.push((path.to_owned(), value));
+                            }
+                        } else if let Some(value) = find_variable_path(&sb_frame, &var) {
+                            event.write_sb_value_renamed(rwriter, &var, &value);
                         }
                     }
                 }
                 VariableCapture::None => (),
             }
 
-            producer
-                .send_to(&event_stream, event)
-                .context("Fail to stream event")?;
+            let condition = if bp_id == FIREDBG_TRACE_BP_ID {
+                if params.dbg_conditions.is_empty() {
+                    None
+                } else {
+                    match EventStream::try_read_from(event.clone()) {
+                        Ok(Event::Breakpoint { locals, .. }) => locals
+                            .first()
+                            .and_then(|(name, _)| params.dbg_conditions.get(name))
+                            .map(|condition| (condition, locals)),
+                        Ok(_) => None,
+                        // The value is recorded as it is
+                        Err(err) => {
+                            log::warn!("Fail to evaluate condition: {err}");
+                            None
+                        }
+                    }
+                }
+            } else {
+                bp_conditions.get(&bp_id).and_then(|condition| {
+                    let values = match EventStream::try_read_from(event.clone()) {
+                        Ok(Event::FunctionCall { arguments, .. }) => arguments,
+                        Ok(Event::Breakpoint { locals, .. }) => locals,
+                        Ok(Event::FunctionReturn { .. } | Event::FunctionUnwind { .. }) => {
+                            Vec::new()
+                        }
+                        // The hit is recorded as it is
+                        Err(err) => {
+                            log::warn!("Fail to evaluate condition: {err}");
+                            return None;
+                        }
+                    };
+                    Some((condition, values))
+                })
+            };
+            let mut skipped = false;
+            let mut deferred = false;
+            if let Some((condition, values)) = condition {
+                // A condition on the return value is evaluated when the function returns
+                deferred = is_function_call && condition.refers_to(RETVAL);
+                if deferred || !condition.eval(&values) {
+                    skipped = true;
+                    watch_requests.clear();
+                    if is_function_call {
+                        // Skip the return of this call as well, and it does not count towards `MAX_HITS_PER_FUNCTION`;
+                        // the frame is still popped when the function returns
+                        let active_frame = active_frames.last_mut().expect("Pushed");
+                        deferred = deferred && active_frame.recorded;
+                        if active_frame.recorded {
+                            active_frame.recorded = false;
+                            function_hits.get_mut(&bp_id).expect("Inserted").recorded -= 1;
+                            truncate = false;
+                        }
+                    }
+                }
+            }
+
+            if !skipped {
+                stream_event(&producer, &event_stream, active_frames, event)?;
+            } else if deferred {
+                // The events inside the call are held back with it
+                let active_frame = active_frames.last_mut().expect("Pushed");
+                active_frame.deferred = Some(DeferredCall {
+                    bp_id,
+                    call: event,
+                    events: Vec::new(),
+                });
+            }
+
+            if let Some(active_frame) = active_frames.last().filter(|active_frame| {
+                is_function_call && (active_frame.recorded || active_frame.deferred.is_some())
+            }) {
+                if let Some(future) = active_frame.future {
+                    let mut event = EventStream::breakpoint(
+                        bp_id,
+                        thread_id,
+                        active_frame.frame_id,
+                        Reason::FuturePoll,
+                    );
+                    let fn_name = active_frame.function_name.replace("::{{closure}}", "");
+                    event.write_string(rwriter, "fn", &fn_name);
+                    event.write_u64(rwriter, "future", future);
+                    let polls = future_polls.get(&future).map_or(0, |(_, polls)| *polls);
+                    event.write_u64(rwriter, "poll", polls);
+                    stream_event(&producer, &event_stream, active_frames, event)?;
+                }
+            }
+
+            for (name, sb_value) in watch_requests {
+                match watch_value(sb_target, &sb_thread, active_frames, &sb_value) {
+                    Ok(watched) => {
+                        log::debug!(
+                            "Set watchpoint {} on `{name}` at {}",
+                            watched.watchpoint_id,
+                            crate::Addr::new(&watched.address.to_ne_bytes()),
+                        );
+                        let bp = Breakpoint {
+                            id: breakpoints.len() as u32,
+                            file_id: FIREDBG_SOURCE_FILE_ID,
+                            breakpoint_type: BreakpointType::Breakpoint,
+                            capture: VariableCapture::Only(vec![name]),
+                            ..Default::default()
+                        };
+                        send_breakpoint(&bp)?;
+                        watched_values.insert(BpId(bp.id), watched);
+                        breakpoints.push(bp);
+                    }
+                    // e.g. running out of hardware watchpoints
+                    Err(err) => log::warn!("Fail to watch `{name}`: {err}"),
+                }
+            }
+
+            if truncate {
+                // Stop hitting this breakpoint; the `ret` breakpoints are disabled after all active frames returned
+                set_breakpoint_enabled(sb_target, bp_id, false);
+                let functions = &function_hits.get(&bp_id).expect("Inserted").functions;
+                truncated_functions.extend(functions.iter().copied());
+                let active_frame = active_frames.last().expect("Pushed");
+                let mut event = EventStream::breakpoint(
+                    bp_id,
+                    thread_id,
+                    active_frame.frame_id,
+                    Reason::Truncated,
+                );
+                event.write_string(rwriter, "fn", &active_frame.function_name);
+                stream_event(&producer, &event_stream, active_frames, event)?;
+            }
         }
 
         *allocating = None; // any intermediate breakpoint will clear this
 
+        if is_function_return
+            && (!whole_run
+                || !truncated_functions.is_empty()
+                    && truncated_functions.contains(&sb_frame.function().id()))
+        {
+            // The `ret` breakpoints of a truncated function, or any function outside of the recording window
+            // or called before attaching, can be hit by an untraced call;
+            // it is either deeper than the last active frame, or returns when there is no active frame
+            match active_frames.last() {
+                Some(active_frame) if sb_frame.sp() >= active_frame.stack_pointer => (),
+                _ => return Ok(()),
+            }
+        }
+
         if is_function_return || return_immediately {
             // We should have at least one active frame because we're handling a function return
             assert!(!active_frames.is_empty());
@@ -779,53 +1548,168 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                 }
             }
 
-            let last_frame = active_frames.pop().expect("Not empty");
+            let mut last_frame = active_frames.pop().expect("Not empty");
             if !return_immediately {
                 assert!(sb_frame.sp() >= last_frame.stack_pointer);
             }
+            drop_watched_values(sb_target, watched_values, thread_id, last_frame.frame_id);
+            let deferred = last_frame.deferred.take();
+            if last_frame.recorded || deferred.is_some() {
+                let mut event = EventStream::function_return(bp_id, thread_id, &last_frame, &clock);
+                if last_frame.call_only {
+                    event.write_value(rwriter, RETVAL, rwriter.opaque_v().as_bytes());
+                } else if write_return_value(
+                    &mut event,
+                    rwriter,
+                    sb_target,
+                    sb_process,
+                    &sb_frame,
+                    fn_cache.get(&last_frame.function_id).expect("Cached"),
+                )
+                .is_err()
+                {
+                    event.write_value(rwriter, RETVAL, rwriter.opaque_v().as_bytes());
+                }
 
-            let mut event = EventStream::function_return(bp_id, thread_id, &last_frame);
-            if write_return_value(
-                &mut event,
-                rwriter,
-                sb_target,
-                sb_process,
-                &sb_frame,
-                fn_cache.get(&last_frame.function_id).expect("Cached"),
-            )
-            .is_err()
-            {
-                event.write_value(rwriter, RETVAL, rwriter.opaque_v().as_bytes());
+                let mut record = true;
+                if let Some(deferred) = deferred {
+                    // Evaluate the condition on the arguments and the return value
+                    let values =
+                        EventStream::try_read_from(deferred.call.clone()).and_then(|call| {
+                            let mut values = match call {
+                                Event::FunctionCall { arguments, .. } => arguments,
+                                _ => Vec::new(),
+                            };
+                            if let Event::FunctionReturn { return_value, .. } =
+                                EventStream::try_read_from(event.clone())?
+                            {
+                                values.push((RETVAL.to_owned(), return_value));
+                            }
+                            Ok(values)
+                        });
+                    record = match values {
+                        Ok(values) => bp_conditions
+                            .get(&deferred.bp_id)
+                            .map_or(false, |condition| condition.eval(&values)),
+                        // The call is recorded as it is
+                        Err(err) => {
+                            log::warn!("Fail to evaluate condition: {err}");
+                            true
+                        }
+                    };
+                    if record {
+                        function_hits
+                            .get_mut(&deferred.bp_id)
+                            .expect("Inserted")
+                            .recorded += 1;
+                        for event in std::iter::once(deferred.call).chain(deferred.events) {
+                            stream_event(&producer, &event_stream, active_frames, event)?;
+                        }
+                    }
+                }
+                if record {
+                    stream_event(&producer, &event_stream, active_frames, event)?;
+                }
             }
 
-            producer
-                .send_to(&event_stream, event)
-                .context("Fail to stream return value")?;
+            release_return_breakpoints(
+                sb_target,
+                &threads,
+                &truncated_functions,
+                &mut return_breakpoints,
+                last_frame.function_id,
+            );
         }
 
         Ok(())
     };
 
-    let sb_process = process_timer
-        .debugger_launch
-        .time(|| sb_target.launch_redirected(stdout_path, stderr_path, params.arguments))
-        .context("Fail to launch debugger")?;
+    let sb_process = match params.pid {
+        Some(pid) => process_timer
+            .debugger_launch
+            .time(|| sb_target.attach_to_process_with_id(pid, &SBListener::new()))
+            .with_context(|| format!("Fail to attach to process: `{pid}`"))?,
+        None => {
+            // Inherit the environment, unless there is something to change
+            let env: Option<Vec<String>> = if params.env.is_empty() && params.env_remove.is_empty()
+            {
+                None
+            } else {
+                let inherited = std::env::vars_os().filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                });
+                Some(
+                    inherited
+                        .filter(|(key, _)| {
+                            !params.env_remove.contains(key) && !params.env.contains_key(key)
+                        })
+                        .chain(params.env.clone())
+                        .map(|(key, value)| format!("{key}={value}"))
+                        .collect(),
+                )
+            };
+            process_timer
+                .debugger_launch
+                .time(|| {
+                    sb_target.launch_redirected(
+                        stdin_fifo,
+                        stdout_path,
+                        stderr_path,
+                        params.arguments,
+                        env.as_deref(),
+                        params.cwd.as_deref(),
+                    )
+                })
+                .context("Fail to launch debugger")?
+        }
+    };
     let sb_process = unsafe {
         SB_PROCESS = Some(sb_process);
         SB_PROCESS.as_ref().expect("Some")
     };
 
+    if params.recording.on_signal {
+        let mut result = SBCommandReturnObject::new();
+        sb_debugger.command_interpreter().handle_command(
+            "process handle SIGUSR1 --stop true --pass false --notify false",
+            &mut result,
+            false,
+        );
+        if !result.succeeded() {
+            log::warn!("Fail to handle SIGUSR1: {:?}", result.error());
+        }
+    }
+    let spawn_recording_timer = |delay: Duration, recording: bool| {
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            request_recording(recording);
+        });
+    };
+    if let Some(after) = params.recording.after {
+        spawn_recording_timer(after, true);
+    }
+    if let Some(length) = params.recording.length {
+        spawn_recording_timer(params.recording.after.unwrap_or_default() + length, false);
+    }
+
     let t_debugger_run = process_timer.debugger_run.span();
+    let mut detached = false;
+    // The last signal the program stopped on; cleared once the program reaches a breakpoint again
+    let mut fatal_stop: Option<FatalStop> = None;
 
     loop {
         match sb_process.state() {
             // We hit a breakpoint!
             ProcessState::Stopped => {}
+            // The process crashed and is still around, capture the backtrace before shutting down
+            ProcessState::Crashed => {
+                if fatal_stop.is_none() {
+                    fatal_stop = Some(FatalStop::new(sb_process, &sb_process.selected_thread()));
+                }
+                break;
+            }
             // The process we're debugging ended, we should shutdown the debugger
-            ProcessState::Unloaded
-            | ProcessState::Crashed
-            | ProcessState::Detached
-            | ProcessState::Exited => break,
+            ProcessState::Unloaded | ProcessState::Detached | ProcessState::Exited => break,
             // Nothing of interest, we will wait for a breakpoint hit
             ProcessState::Invalid
             | ProcessState::Connected
@@ -836,6 +1720,13 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
             | ProcessState::Suspended => continue,
         }
         let sb_thread = sb_process.selected_thread();
+        if matches!(
+            sb_thread.stop_reason(),
+            StopReason::Breakpoint | StopReason::Watchpoint
+        ) {
+            // The program survived the signal
+            fatal_stop = None;
+        }
         if matches!(sb_thread.stop_reason(), StopReason::Breakpoint) {
             // Parse the breakpoint index from stop description
             // A typical stop reason looks like `breakpoint 1.1`, we want the first `1` out of it.
@@ -844,9 +1735,19 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
             let breakpoint_id = BpId(sb_thread.stop_reason_data_at_index(0) as u32);
             log::debug!("Stop at Breakpoint {breakpoint_id:?}");
             let selected_thread_id = sb_thread.thread_id();
-            let bp_addrs = &mut breakpoint_addresses;
-            handle_breakpoint(bp_addrs, sb_process, sb_thread, breakpoint_id)
-                .context("Fail to handle breakpoint")?;
+            // The time the target has been running; the time spent in the debugger is excluded
+            let target_time =
+                process_timer.debugger_launch.elapsed + process_timer.process_resume.elapsed;
+            let (bp_addrs, watched) = (&mut breakpoint_addresses, &mut watched_values);
+            handle_breakpoint(
+                bp_addrs,
+                watched,
+                sb_process,
+                sb_thread,
+                breakpoint_id,
+                target_time,
+            )
+            .context("Fail to handle breakpoint")?;
 
             // handle other threads
             for i in 0..sb_process.num_threads() {
@@ -859,14 +1760,57 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
                 // the other thread is also stopped at a breakpoint
                 if let Some(bp_id) = breakpoint_addresses.get(&pc_file_address).copied() {
                     log::debug!("Handle extra breakpoint for {bp_id:?}");
-                    let bp_addrs = &mut breakpoint_addresses;
-                    handle_breakpoint(bp_addrs, sb_process, sb_thread, bp_id)
+                    let (bp_addrs, watched) = (&mut breakpoint_addresses, &mut watched_values);
+                    handle_breakpoint(bp_addrs, watched, sb_process, sb_thread, bp_id, target_time)
                         .context("Fail to handle breakpoint")?;
                 }
             }
+        } else if matches!(sb_thread.stop_reason(), StopReason::Watchpoint) {
+            let watchpoint_id = sb_thread.stop_reason_data_at_index(0) as WatchpointID;
+            let bp_id = watched_values
+                .iter()
+                .find(|(_, watched)| watched.watchpoint_id == watchpoint_id)
+                .map(|(bp_id, _)| *bp_id);
+            if let Some(bp_id) = bp_id {
+                log::debug!("Stop at Watchpoint {watchpoint_id} {bp_id:?}");
+                let target_time =
+                    process_timer.debugger_launch.elapsed + process_timer.process_resume.elapsed;
+                let (bp_addrs, watched) = (&mut breakpoint_addresses, &mut watched_values);
+                handle_breakpoint(bp_addrs, watched, sb_process, sb_thread, bp_id, target_time)
+                    .context("Fail to handle watchpoint")?;
+            }
+        } else if params.recording.on_signal
+            && matches!(sb_thread.stop_reason(), StopReason::Signal)
+            && sb_thread.stop_reason_data_at_index(0) == libc::SIGUSR1 as u64
+        {
+            let mut request = RECORDING_REQUEST.lock().expect("Not poisoned");
+            *request = Some(!request.unwrap_or(RECORDING.load(Ordering::SeqCst)));
+        } else if matches!(
+            sb_thread.stop_reason(),
+            StopReason::Signal | StopReason::Exception
+        ) {
+            log::debug!("Stop for {}", sb_thread.stop_description());
+            // The signal is delivered on resume, which may terminate the program
+            fatal_stop = Some(FatalStop::new(sb_process, &sb_thread));
         } else {
             log::debug!("Stop for some reason {:?}", sb_thread.stop_reason());
         }
+        if let Some(recording) = RECORDING_REQUEST.lock().expect("Not poisoned").take() {
+            if recording != RECORDING.swap(recording, Ordering::SeqCst) {
+                log::info!(
+                    "Recording {}",
+                    if recording { "started" } else { "stopped" }
+                );
+                for bp_id in recording_breakpoints.iter() {
+                    set_breakpoint_enabled(sb_target, *bp_id, recording);
+                }
+            }
+        }
+        if DETACH.load(Ordering::SeqCst) {
+            sb_process.detach().context("Fail to detach from process")?;
+            detached = true;
+            break;
+        }
         log::trace!("sb_process.resume");
         process_timer
             .process_resume
@@ -874,11 +1818,48 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
             .context("Fail to resume debugger")?;
     }
 
-    let exit_code = sb_process.exit_status();
+    // Whatever is written after the last stop
+    for tail in output_tails.iter_mut() {
+        tail.end();
+    }
+    let target_time = process_timer.debugger_launch.elapsed + process_timer.process_resume.elapsed;
+    let clock = target_time.as_nanos() as u64;
+    send_output(&producer, &output_stream, &mut output_tails, 0, 0, clock)?;
+    if stdin_fifo.is_some() {
+        std::fs::remove_file(stdin_path).ok();
+    }
+
+    // The process keeps running after detach, so there is no exit status
+    let exit_code = if detached {
+        0
+    } else {
+        sb_process.exit_status()
+    };
+
+    let exit_description = match sb_process.exit_description() {
+        "" => fatal_stop.as_ref().map(|stop| stop.description.clone()),
+        description => Some(description.to_owned()),
+    };
+    let (signal, backtrace) = match fatal_stop {
+        Some(FatalStop {
+            signal, backtrace, ..
+        }) if !detached => (signal, backtrace),
+        _ => (None, Vec::new()),
+    };
+    if let Some(signal) = &signal {
+        log::info!("Program terminated by {} ({})", signal.name, signal.number);
+    }
 
     producer.send_to(
         &StreamKey::new(INFO_STREAM)?,
-        serde_json::to_string(&InfoMessage::Exit(ProgExitInfo { exit_code }))?.as_str(),
+        serde_json::to_string(&InfoMessage::Exit(ProgExitInfo {
+            exit_code,
+            detached,
+            signal,
+            exit_description,
+            backtrace,
+        }))?
+        .as_str(),
     )?;
 
     // Fail to start LLDB
@@ -887,6 +1868,11 @@ fn run(mut params: DebuggerParams, mut producer: SeaProducer) -> Result<()> {
     }
 
     std::mem::drop(t_debugger_run);
+    {
+        // Wait for any pending `stop_process` to return
+        let _request = RECORDING_REQUEST.lock().expect("Not poisoned");
+        FINISHED.store(true, Ordering::SeqCst);
+    }
     process_timer.debugger_cleanup.time(SBDebugger::terminate);
     log::debug!("Debugger Terminated");
 
@@ -1126,6 +2112,14 @@ impl Bytes {
         self.write_value(rwriter, name, bytes.as_bytes());
     }
 
+    fn write_u64(&mut self, rwriter: &mut RValueWriter, name: &str, value: u64) {
+        self.identifier(name);
+        self.push_str("name");
+        self.space();
+        self.push_bytes(rwriter.prim_v("u64", &value.to_ne_bytes()));
+        self.space();
+    }
+
     fn write_string(&mut self, rwriter: &mut RValueWriter, name: &str, value: &str) {
         self.identifier(name);
         self.push_str("name");
@@ -1141,6 +2135,7 @@ impl Default for Thread {
             frame: Frame { frame_id: 0 },
             active_frames: Vec::new(),
             allocating: None,
+            pending_alloc: None,
         }
     }
 }
@@ -1160,6 +2155,42 @@ pub fn new_breakpoint(id: u32, file_id: u32, func: &FunctionDef) -> Breakpoint {
 }
 
 #[doc(hidden)]
+/// Breakpoint of a function in a `call-only` package; arguments and return value are not captured
+pub fn new_call_only_breakpoint(id: u32, file_id: u32, func: &FunctionDef) -> Breakpoint {
+    Breakpoint {
+        capture: VariableCapture::None,
+        ..new_breakpoint(id, file_id, func)
+    }
+}
+
+#[doc(hidden)]
+/// Breakpoint of a statement in a `statements` package; all local variables are captured
+pub fn new_statement_breakpoint(id: u32, file_id: u32, line: u32) -> Breakpoint {
+    Breakpoint {
+        id,
+        file_id,
+        loc: LineColumn { line, column: None },
+        loc_end: None,
+        breakpoint_type: BreakpointType::Breakpoint,
+        capture: VariableCapture::Locals,
+    }
+}
+
+#[doc(hidden)]
+/// Breakpoint of a watch expression; only the given variables or field paths are captured
+pub fn new_watch_breakpoint(id: u32, file_id: u32, line: u32, vars: Vec<String>) -> Breakpoint {
+    Breakpoint {
+        id,
+        file_id,
+        loc: LineColumn { line, column: None },
+        loc_end: None,
+        breakpoint_type: BreakpointType::Breakpoint,
+        capture: VariableCapture::Only(vars),
+    }
+}
+
+#[doc(hidden)]
+/// Breakpoint at the end of an `async fn`; it must follow the breakpoint of the function
 pub fn new_async_breakpoint(id: u32, file_id: u32, func: &FunctionDef) -> Breakpoint {
     Breakpoint {
         id,
@@ -1171,6 +2202,177 @@ pub fn new_async_breakpoint(id: u32, file_id: u32, func: &FunctionDef) -> Breakp
     }
 }
 
+/// Find a variable, or a field of it by path, e.g. `self.len` or `point.0`
+fn find_variable_path(sb_frame: &SBFrame, path: &str) -> Option<SBValue> {
+    let find_member = |value: &SBValue, name: &str| {
+        value
+            .child_member_with_name(name)
+            // Fields of tuples are named `__0`, `__1`, ...
+            .or_else(|| value.child_member_with_name(&format!("__{name}")))
+    };
+    let mut segments = path.split('.');
+    let mut value = sb_frame.find_variable(segments.next()?)?;
+    for segment in segments {
+        value = find_member(&value, segment)
+            // Auto deref, e.g. `self` is `&Self`
+            .or_else(|| find_member(&value.dereference(), segment))?;
+    }
+    Some(value)
+}
+
+fn set_breakpoint_enabled(sb_target: &SBTarget, bp_id: BpId, enabled: bool) {
+    if let Some(sb_bp) = sb_target.find_breakpoint_by_id(bp_id.0) {
+        sb_bp.set_enabled(enabled);
+    }
+}
+
+/// The innermost recorded frame which has been streamed, i.e. not inside a call held back by its condition
+fn owning_frame_id(active_frames: &[ActiveFrame]) -> Option<u64> {
+    let streamed = active_frames
+        .iter()
+        .position(|active_frame| active_frame.deferred.is_some())
+        .unwrap_or(active_frames.len());
+    active_frames[..streamed]
+        .iter()
+        .rev()
+        .find(|active_frame| active_frame.recorded)
+        .map(|active_frame| active_frame.frame_id)
+}
+
+/// The innermost frame whose `FunctionCall` is streamed, now or when its condition is satisfied
+fn event_frame_id(active_frames: &[ActiveFrame]) -> Option<u64> {
+    active_frames
+        .iter()
+        .rev()
+        .find(|active_frame| active_frame.recorded || active_frame.deferred.is_some())
+        .map(|active_frame| active_frame.frame_id)
+}
+
+/// Stream the event, or hold it back with the innermost call whose condition is evaluated when it returns
+fn stream_event(
+    producer: &SeaProducer,
+    event_stream: &StreamKey,
+    active_frames: &mut [ActiveFrame],
+    event: Bytes,
+) -> Result<()> {
+    match active_frames
+        .iter_mut()
+        .rev()
+        .find_map(|active_frame| active_frame.deferred.as_mut())
+    {
+        Some(deferred) => deferred.events.push(event),
+        None => {
+            producer
+                .send_to(event_stream, event)
+                .context("Fail to stream event")?;
+        }
+    }
+    Ok(())
+}
+
+/// Install a write watchpoint on the memory of `sb_value`
+fn watch_value(
+    sb_target: &SBTarget,
+    sb_thread: &SBThread,
+    active_frames: &[ActiveFrame],
+    sb_value: &SBValue,
+) -> Result<WatchedValue> {
+    let address = sb_value
+        .address()
+        .context("Value has no address")?
+        .load_address(sb_target);
+    let size = sb_value.byte_size();
+    anyhow::ensure!(size > 0, "Zero-sized value");
+    let bytes = read_process_memory(address, size)
+        .with_context(|| format!("Fail to read memory: `{address}`"))?;
+    let watchpoint_id = sb_target
+        .watch_address(address, size, false, true)
+        .map_err(|err| anyhow::anyhow!("{err}"))?
+        .id();
+    // A stack value lies between the current and the outermost stack pointer;
+    // the watchpoint is removed when the innermost traced frame below it returns
+    let on_stack = address >= sb_thread.selected_frame().sp()
+        && sb_thread
+            .frames()
+            .last()
+            .map_or(false, |sb_frame| address < sb_frame.sp());
+    let scope = if on_stack {
+        active_frames
+            .iter()
+            .rev()
+            .find(|active_frame| active_frame.stack_pointer <= address)
+            .map(|active_frame| (sb_thread.thread_id(), active_frame.frame_id))
+    } else {
+        None
+    };
+    Ok(WatchedValue {
+        watchpoint_id,
+        address,
+        sb_type: sb_value.type_(),
+        bytes,
+        scope,
+    })
+}
+
+/// Values on the stack of a popped frame are gone
+fn drop_watched_values(
+    sb_target: &SBTarget,
+    watched_values: &mut FxHashMap<BpId, WatchedValue>,
+    thread_id: u64,
+    frame_id: u64,
+) {
+    watched_values.retain(|_, watched| {
+        if watched.scope == Some((thread_id, frame_id)) {
+            sb_target.delete_watchpoint(watched.watchpoint_id);
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Disable the `ret` breakpoints of a truncated function once no thread is inside it
+fn release_return_breakpoints(
+    sb_target: &SBTarget,
+    threads: &FxHashMap<u64, Thread>,
+    truncated_functions: &FxHashSet<SBFunctionId>,
+    return_breakpoints: &mut FxHashMap<SBFunctionId, Vec<BpId>>,
+    function_id: SBFunctionId,
+) {
+    if truncated_functions.contains(&function_id)
+        && !threads.values().any(|thread| {
+            thread
+                .active_frames
+                .iter()
+                .any(|active_frame| active_frame.function_id == function_id)
+        })
+    {
+        // No one is going to return from this function anymore
+        for bp_id in return_breakpoints.remove(&function_id).unwrap_or_default() {
+            set_breakpoint_enabled(sb_target, bp_id, false);
+        }
+    }
+}
+
+/// The frame which called `catch_unwind`, when stopped at `__rust_panic_cleanup`
+fn find_panic_catcher(sb_thread: &SBThread) -> Option<SBFrame> {
+    let mut catcher = None;
+    let mut in_catch = false;
+    for sb_frame in sb_thread.frames() {
+        let function_name = sb_frame.function_name().unwrap_or_default();
+        if PANIC_CATCH_FUNCTIONS
+            .iter()
+            .any(|prefix| function_name.starts_with(prefix))
+        {
+            in_catch = true;
+        } else if in_catch {
+            catcher = Some(sb_frame);
+            break;
+        }
+    }
+    catcher
+}
+
 fn register_breakpoint(breakpoint_addresses: &mut FxHashMap<u64, BpId>, sb_bp: &SBBreakpoint) {
     for sb_bp_loc in sb_bp.locations() {
         breakpoint_addresses.insert(sb_bp_loc.address().file_address() as u64, BpId(sb_bp.id()));
@@ -1208,6 +2410,28 @@ fn get_firedbg_trace_breakpoint() -> Breakpoint {
     }
 }
 
+fn get_firedbg_watch_breakpoint() -> Breakpoint {
+    Breakpoint {
+        id: FIREDBG_WATCH_BP_ID.0,
+        file_id: FIREDBG_SOURCE_FILE_ID,
+        loc: Default::default(),
+        loc_end: Default::default(),
+        breakpoint_type: BreakpointType::Breakpoint,
+        capture: VariableCapture::Arguments,
+    }
+}
+
+fn get_rust_panic_cleanup_breakpoint() -> Breakpoint {
+    Breakpoint {
+        id: RUST_PANIC_CLEANUP_BP_ID.0,
+        file_id: FIREDBG_SOURCE_FILE_ID,
+        loc: Default::default(),
+        loc_end: Default::default(),
+        breakpoint_type: BreakpointType::Breakpoint,
+        capture: VariableCapture::None,
+    }
+}
+
 fn get_exchange_malloc_breakpoint() -> Breakpoint {
     Breakpoint {
         id: EXCHANGE_MALLOC.0,
@@ -1219,6 +2443,17 @@ fn get_exchange_malloc_breakpoint() -> Breakpoint {
     }
 }
 
+fn get_allocator_breakpoint(bp_id: BpId) -> Breakpoint {
+    Breakpoint {
+        id: bp_id.0,
+        file_id: FIREDBG_SOURCE_FILE_ID,
+        loc: Default::default(),
+        loc_end: Default::default(),
+        breakpoint_type: BreakpointType::Breakpoint,
+        capture: VariableCapture::Arguments,
+    }
+}
+
 fn get_drop_in_place_breakpoint() -> Breakpoint {
     Breakpoint {
         id: DROP_IN_PLACE.0,
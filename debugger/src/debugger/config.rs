@@ -34,7 +34,48 @@ lazy_static::lazy_static! {
     pub static ref KEEP_HASH_ORDER: bool = config_bool("KEEP_HASH_ORDER");
     /// If set, don't trace heap allocations.
     pub static ref DONT_TRACE_ALLOCATION: bool = config_bool("DONT_TRACE_ALLOCATION");
-    static ref SAMPLE: Option<String> = config_string("SAMPLE");
+    /// If set, record the CPU time of the thread on each function call and return. Linux only.
+    pub static ref RECORD_CPU_TIME: bool = config_bool("RECORD_CPU_TIME");
+    /// If set, stop tracing a function after it has been called this many times.
+    pub static ref MAX_HITS_PER_FUNCTION: Option<usize> = config_usize("MAX_HITS_PER_FUNCTION");
+    /// If set, only record a sample of the function calls of each function.
+    pub static ref SAMPLE: Option<Sample> = config_string("SAMPLE").and_then(|s| {
+        let sample = Sample::parse(&s);
+        if sample.is_none() {
+            log::warn!("Invalid SAMPLE config: `{s}`");
+        }
+        sample
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Sampling mode of function calls
+pub enum Sample {
+    /// `SAMPLE=k/n`: record `k` out of every `n` calls
+    Ratio(u64, u64),
+    /// `SAMPLE=first:n`: record only the first `n` calls
+    First(u64),
+}
+
+impl Sample {
+    fn parse(s: &str) -> Option<Self> {
+        if let Some(n) = s.strip_prefix("first:") {
+            return n.parse().ok().map(Self::First);
+        }
+        let (k, n) = s.split_once('/')?;
+        match (k.parse(), n.parse()) {
+            (Ok(k), Ok(n)) if n > 0 => Some(Self::Ratio(k, n)),
+            _ => None,
+        }
+    }
+
+    /// Whether the `hit`-th (zero based) call of a function should be recorded
+    pub fn record(&self, hit: u64) -> bool {
+        match *self {
+            Self::Ratio(k, n) => hit % n < k,
+            Self::First(n) => hit < n,
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -72,3 +113,22 @@ enum ConfigValue {
     bool(bool),
     u64(u64),
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        assert_eq!(Sample::parse("1/100"), Some(Sample::Ratio(1, 100)));
+        assert_eq!(Sample::parse("first:50"), Some(Sample::First(50)));
+        assert_eq!(Sample::parse("1/0"), None);
+        assert_eq!(Sample::parse("first"), None);
+        assert_eq!(Sample::parse("all"), None);
+
+        let hits: Vec<u64> = (0..10).filter(|i| Sample::Ratio(1, 4).record(*i)).collect();
+        assert_eq!(hits, [0, 4, 8]);
+        let hits: Vec<u64> = (0..10).filter(|i| Sample::First(3).record(*i)).collect();
+        assert_eq!(hits, [0, 1, 2]);
+    }
+}
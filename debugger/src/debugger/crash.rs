@@ -0,0 +1,236 @@
+use super::read_process_memory;
+use crate::{value::read_str, LineColumn, PanicLocation};
+use firedbg_protocol::info::{BacktraceFrame, ExitSignal, ThreadBacktrace};
+use lldb::{SBProcess, SBThread, SBValue, StopReason};
+
+/// How deep into the panic machinery to look for the panic message and location
+const MAX_PANIC_FRAMES: usize = 16;
+
+/// The program stopped on a signal (or an exception on macOS) which may terminate it
+pub(super) struct FatalStop {
+    pub(super) signal: Option<ExitSignal>,
+    /// e.g. `signal SIGSEGV: invalid address (fault address: 0x0)`
+    pub(super) description: String,
+    pub(super) backtrace: Vec<ThreadBacktrace>,
+}
+
+impl FatalStop {
+    /// `sb_thread` is the thread that received the signal
+    pub(super) fn new(sb_process: &SBProcess, sb_thread: &SBThread) -> Self {
+        let signal = match sb_thread.stop_reason() {
+            StopReason::Signal => {
+                let number = sb_thread.stop_reason_data_at_index(0) as i32;
+                Some(ExitSignal {
+                    number,
+                    name: signal_name(number).unwrap_or_default().to_owned(),
+                })
+            }
+            _ => None,
+        };
+        Self {
+            signal,
+            description: sb_thread.stop_description(),
+            backtrace: backtrace(sb_process),
+        }
+    }
+}
+
+/// Backtrace of every thread of the stopped process
+pub(super) fn backtrace(sb_process: &SBProcess) -> Vec<ThreadBacktrace> {
+    sb_process
+        .threads()
+        .map(|sb_thread| ThreadBacktrace {
+            thread_id: sb_thread.thread_id(),
+            thread_name: sb_thread.name().map(ToOwned::to_owned),
+            frames: thread_backtrace(&sb_thread),
+        })
+        .collect()
+}
+
+/// Backtrace of a stopped thread, innermost frame first
+pub(super) fn thread_backtrace(sb_thread: &SBThread) -> Vec<BacktraceFrame> {
+    sb_thread
+        .frames()
+        .map(|sb_frame| {
+            let line_entry = sb_frame.line_entry();
+            BacktraceFrame {
+                pc: sb_frame.pc(),
+                function_name: sb_frame.function_name().map(ToOwned::to_owned),
+                file: line_entry
+                    .as_ref()
+                    .map(|line_entry| line_entry.file_spec().path().display().to_string()),
+                loc: line_entry.map(|line_entry| LineColumn {
+                    line: line_entry.line(),
+                    column: Some(line_entry.column()).filter(|column| *column != 0),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Read the panic message and location off the stack of `rust_panic`. It is only possible if the
+/// standard library has debug info; otherwise we parse what the default panic hook has printed.
+pub(super) fn read_panic(
+    sb_thread: &SBThread,
+    stderr: &str,
+) -> (Option<String>, Option<PanicLocation>) {
+    let mut message = None;
+    let mut location = None;
+    for sb_frame in sb_thread.frames().take(MAX_PANIC_FRAMES) {
+        if location.is_none() {
+            // `rust_panic_with_hook(payload, .., location, ..)`
+            location = sb_frame
+                .find_variable("location")
+                .and_then(|location| read_location(&location.dereference()));
+        }
+        if message.is_none() {
+            let function_name = sb_frame.function_name().unwrap_or_default();
+            if function_name.starts_with("core::panicking::panic_fmt") {
+                message = sb_frame
+                    .find_variable("fmt")
+                    .and_then(|fmt| read_fmt_arguments(&fmt));
+            } else if function_name.starts_with("std::panicking::begin_panic<") {
+                // `panic!` of the 2015 / 2018 editions and `panic_any` with a `&str` payload
+                message = sb_frame
+                    .find_variable("msg")
+                    .filter(|msg| msg.type_name() == Some("&str"))
+                    .and_then(|msg| read_str(&msg).ok())
+                    .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+            }
+        }
+    }
+    if message.is_none() || location.is_none() {
+        if let Some((hook_message, hook_location)) = parse_panic_hook(stderr) {
+            message = message.or(Some(hook_message));
+            location = location.or(hook_location);
+        }
+    }
+    (message, location)
+}
+
+/// Read a `core::panic::Location`
+fn read_location(location: &SBValue) -> Option<PanicLocation> {
+    let file = read_str(&location.child_member_with_name("file")?).ok()?;
+    Some(PanicLocation {
+        file: String::from_utf8_lossy(&file).into_owned(),
+        line: location
+            .child_member_with_name("line")?
+            .value_as_unsigned(0) as u32,
+        column: location.child_member_with_name("col")?.value_as_unsigned(0) as u32,
+    })
+}
+
+/// Read a `core::fmt::Arguments`; the arguments are opaque, so they are shown as `{}`
+fn read_fmt_arguments(fmt: &SBValue) -> Option<String> {
+    let pieces = fmt.child_member_with_name("pieces")?;
+    let num_pieces = pieces.child_at_index(1).value_as_unsigned(0);
+    let num_args = fmt
+        .child_member_with_name("args")?
+        .child_at_index(1)
+        .value_as_unsigned(0);
+    let pieces_addr = pieces.child_at_index(0).value_as_unsigned(0);
+    let mut message = String::new();
+    for i in 0..num_pieces.max(num_args) {
+        if i < num_pieces {
+            // Each piece is a `&str` of (ptr, len)
+            let piece = read_process_memory(pieces_addr + i * 16, 16).ok()?;
+            let ptr = u64::from_ne_bytes(piece[..8].try_into().expect("8 bytes"));
+            let len = u64::from_ne_bytes(piece[8..].try_into().expect("8 bytes"));
+            let bytes = read_process_memory(ptr, len as usize).ok()?;
+            message.push_str(&String::from_utf8_lossy(&bytes));
+        }
+        if i < num_args {
+            message.push_str("{}");
+        }
+    }
+    Some(message)
+}
+
+/// Parse what the default panic hook prints, e.g.
+///
+/// ```text
+/// thread 'main' panicked at src/main.rs:2:5:
+/// index out of bounds: the len is 3 but the index is 5
+/// note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+/// ```
+fn parse_panic_hook(stderr: &str) -> Option<(String, Option<PanicLocation>)> {
+    let (_, rest) = stderr.rsplit_once("' panicked at ")?;
+    let (location, message) = rest.split_once(":\n")?;
+    let end = ["\nnote: ", "\nstack backtrace:"]
+        .iter()
+        .filter_map(|trailer| message.find(trailer))
+        .min()
+        .unwrap_or(message.len());
+    let message = message[..end].trim_end_matches('\n').to_owned();
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.parse().ok();
+    let line = parts.next()?.parse().ok();
+    let location = match (parts.next(), line, column) {
+        (Some(file), Some(line), Some(column)) => Some(PanicLocation {
+            file: file.to_owned(),
+            line,
+            column,
+        }),
+        _ => None,
+    };
+    Some((message, location))
+}
+
+/// Name of the signals which may terminate a program
+fn signal_name(number: i32) -> Option<&'static str> {
+    Some(match number {
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGILL => "SIGILL",
+        libc::SIGINT => "SIGINT",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGSYS => "SIGSYS",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGUSR1 => "SIGUSR1",
+        libc::SIGUSR2 => "SIGUSR2",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_panic_hook() {
+        assert_eq!(
+            parse_panic_hook(
+                "hello\nthread 'main' panicked at src/main.rs:2:5:\nindex out of bounds: the len is 3 but the index is 5\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n"
+            ),
+            Some((
+                "index out of bounds: the len is 3 but the index is 5".to_owned(),
+                Some(PanicLocation {
+                    file: "src/main.rs".to_owned(),
+                    line: 2,
+                    column: 5,
+                })
+            ))
+        );
+        assert_eq!(
+            parse_panic_hook(
+                "thread '<unnamed>' panicked at C:\\src\\lib.rs:10:9:\nfirst line\nsecond line\n"
+            ),
+            Some((
+                "first line\nsecond line".to_owned(),
+                Some(PanicLocation {
+                    file: "C:\\src\\lib.rs".to_owned(),
+                    line: 10,
+                    column: 9,
+                })
+            ))
+        );
+        assert_eq!(parse_panic_hook("nothing to see here\n"), None);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::{Output, OutputChannel};
+use anyhow::{Context, Result};
+use sea_streamer::{Producer, SeaProducer, StreamKey};
+use std::{
+    ffi::CString,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    os::unix::io::{AsRawFd, RawFd},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Where the stdin of the program comes from
+pub enum StdinSource {
+    /// The program reads nothing
+    #[default]
+    None,
+    /// Feed the content of a file
+    File(String),
+    /// Pass through the stdin of the debugger
+    Inherit,
+}
+
+/// The input fed to the program through a named pipe
+#[derive(Debug)]
+struct Fed {
+    /// Written to the pipe but not yet streamed
+    written: Mutex<Vec<u8>>,
+    /// The write end of the pipe; -1 if not open
+    fifo: Mutex<RawFd>,
+}
+
+impl Default for Fed {
+    fn default() -> Self {
+        Self {
+            written: Default::default(),
+            fifo: Mutex::new(-1),
+        }
+    }
+}
+
+impl Fed {
+    /// Bytes in the pipe not yet read by the program
+    fn unread(&self) -> usize {
+        let fifo = self.fifo.lock().expect("Not poisoned");
+        let mut unread: libc::c_int = 0;
+        if *fifo < 0 || unsafe { libc::ioctl(*fifo, libc::FIONREAD, &mut unread) } != 0 {
+            return 0;
+        }
+        unread as usize
+    }
+
+    /// Take the input the program has read
+    fn take_consumed(&self) -> Vec<u8> {
+        let unread = self.unread();
+        let mut written = self.written.lock().expect("Not poisoned");
+        let consumed = written.len().saturating_sub(unread);
+        written.drain(..consumed).collect()
+    }
+}
+
+/// Follows the program input or output
+pub(super) struct OutputTail {
+    channel: OutputChannel,
+    source: TailSource,
+    /// The start of a char cut short by the last read
+    partial: Vec<u8>,
+    /// Nothing more will be written; a char cut short is decoded as it is
+    ended: bool,
+}
+
+enum TailSource {
+    /// A file the program output is redirected to
+    File {
+        path: String,
+        file: Option<File>,
+        /// Bytes read so far
+        offset: u64,
+    },
+    /// Input fed to the program
+    Fed(Arc<Fed>),
+}
+
+impl OutputTail {
+    pub(super) fn new(channel: OutputChannel, path: &str) -> Self {
+        Self {
+            channel,
+            source: TailSource::File {
+                path: path.to_owned(),
+                file: None,
+                offset: 0,
+            },
+            partial: Vec::new(),
+            ended: false,
+        }
+    }
+
+    /// The program has ended; the next read is the last
+    pub(super) fn end(&mut self) {
+        self.ended = true;
+    }
+
+    /// Read everything written since the last call
+    fn read(&mut self) -> Result<Option<String>> {
+        let bytes = self.read_bytes()?;
+        Ok(self.decode(bytes))
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        let (path, file, offset) = match &mut self.source {
+            TailSource::File { path, file, offset } => (path, file, offset),
+            TailSource::Fed(fed) => return Ok(fed.take_consumed()),
+        };
+        if file.is_none() {
+            match File::open(&path) {
+                Ok(opened) => *file = Some(opened),
+                // The program has not started yet
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+                Err(err) => {
+                    return Err(err).with_context(|| format!("Fail to open file: `{path}`"))
+                }
+            }
+        }
+        let file = file.as_mut().expect("Opened");
+        let len = file.metadata().context("Fail to stat file")?.len();
+        if len <= *offset {
+            return Ok(Vec::new());
+        }
+        let mut bytes = Vec::with_capacity((len - *offset) as usize);
+        file.seek(SeekFrom::Start(*offset))
+            .context("Fail to seek file")?;
+        file.take(len - *offset)
+            .read_to_end(&mut bytes)
+            .context("Fail to read file")?;
+        *offset += bytes.len() as u64;
+        Ok(bytes)
+    }
+
+    /// Decode the bytes read, after the char cut short by the last read.
+    /// A char cut short at the end is kept until the rest of it is read.
+    fn decode(&mut self, bytes: Vec<u8>) -> Option<String> {
+        let mut bytes = if self.partial.is_empty() {
+            bytes
+        } else {
+            let mut partial = std::mem::take(&mut self.partial);
+            partial.extend(bytes);
+            partial
+        };
+        let mut start = 0;
+        while !self.ended {
+            match std::str::from_utf8(&bytes[start..]) {
+                Ok(_) => break,
+                Err(err) => match err.error_len() {
+                    // An invalid sequence in the middle
+                    Some(len) => start += err.valid_up_to() + len,
+                    // A char cut short at the end
+                    None => {
+                        self.partial = bytes.split_off(start + err.valid_up_to());
+                        break;
+                    }
+                },
+            }
+        }
+        if bytes.is_empty() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Create a named pipe at `fifo_path` for the program to read as stdin, and feed it from `source`
+/// on a background thread. Returns the tail of the input the program has read.
+pub(super) fn feed_stdin(fifo_path: &str, source: &StdinSource) -> Result<Option<OutputTail>> {
+    let mut reader: Box<dyn Read + Send> = match source {
+        StdinSource::None => return Ok(None),
+        StdinSource::File(path) => {
+            Box::new(File::open(path).with_context(|| format!("Fail to open file: `{path}`"))?)
+        }
+        StdinSource::Inherit => Box::new(std::io::stdin()),
+    };
+
+    std::fs::remove_file(fifo_path).ok();
+    let c_path = CString::new(fifo_path).context("Invalid path")?;
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Fail to create named pipe: `{fifo_path}`"));
+    }
+
+    let fed: Arc<Fed> = Default::default();
+    let tail = OutputTail {
+        channel: OutputChannel::Stdin,
+        source: TailSource::Fed(fed.clone()),
+        partial: Vec::new(),
+        ended: false,
+    };
+    let fifo_path = fifo_path.to_owned();
+    std::thread::spawn(move || {
+        // Blocks until the program is launched with the pipe as stdin
+        let mut fifo = match OpenOptions::new().write(true).open(&fifo_path) {
+            Ok(fifo) => fifo,
+            Err(err) => {
+                log::warn!("Fail to open named pipe: `{fifo_path}`: {err}");
+                return;
+            }
+        };
+        *fed.fifo.lock().expect("Not poisoned") = fifo.as_raw_fd();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let len = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(len) => len,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    log::warn!("Fail to read stdin: {err}");
+                    break;
+                }
+            };
+            let mut bytes = &buffer[..len];
+            while !bytes.is_empty() {
+                match fifo.write(bytes) {
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    // The program has exited or closed its stdin
+                    Ok(0) | Err(_) => {
+                        *fed.fifo.lock().expect("Not poisoned") = -1;
+                        return;
+                    }
+                    Ok(written) => {
+                        let (written, rest) = bytes.split_at(written);
+                        fed.written
+                            .lock()
+                            .expect("Not poisoned")
+                            .extend_from_slice(written);
+                        bytes = rest;
+                    }
+                }
+            }
+        }
+        // Keep the pipe open until the program has read everything, to tell how much it has read.
+        // Dropping the pipe then signals EOF to the program.
+        while fed.unread() > 0 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        *fed.fifo.lock().expect("Not poisoned") = -1;
+    });
+
+    Ok(Some(tail))
+}
+
+/// Stream the input and output since the last stop; returns what has been streamed
+pub(super) fn send_output(
+    producer: &SeaProducer,
+    output_stream: &StreamKey,
+    tails: &mut [OutputTail],
+    thread_id: u64,
+    frame_id: u64,
+    clock: u64,
+) -> Result<Vec<Output>> {
+    let mut outputs = Vec::new();
+    for tail in tails.iter_mut() {
+        if let Some(content) = tail.read()? {
+            let output = Output {
+                channel: tail.channel,
+                thread_id,
+                frame_id,
+                clock,
+                content,
+            };
+            producer
+                .send_to(output_stream, serde_json::to_string(&output)?)
+                .context("Fail to send output")?;
+            outputs.push(output);
+        }
+    }
+    Ok(outputs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feed(fed: &Arc<Fed>, more: &[u8]) {
+        fed.written.lock().unwrap().extend_from_slice(more);
+    }
+
+    fn stdin_tail(fed: &Arc<Fed>) -> OutputTail {
+        OutputTail {
+            channel: OutputChannel::Stdin,
+            source: TailSource::Fed(fed.clone()),
+            partial: Vec::new(),
+            ended: false,
+        }
+    }
+
+    #[test]
+    fn test_read_split_char() {
+        let fed: Arc<Fed> = Default::default();
+        let mut tail = stdin_tail(&fed);
+        let text = "héllo 世界\n".as_bytes();
+        let (a, b) = text.split_at(2);
+        feed(&fed, a);
+        assert_eq!(tail.read().unwrap().as_deref(), Some("h"));
+        feed(&fed, b);
+        assert_eq!(tail.read().unwrap().as_deref(), Some("éllo 世界\n"));
+        assert_eq!(tail.read().unwrap(), None);
+
+        // Only the start of a char is read
+        feed(&fed, &"世".as_bytes()[..1]);
+        assert_eq!(tail.read().unwrap(), None);
+        feed(&fed, &"世".as_bytes()[1..2]);
+        assert_eq!(tail.read().unwrap(), None);
+        feed(&fed, &"世".as_bytes()[2..]);
+        assert_eq!(tail.read().unwrap().as_deref(), Some("世"));
+
+        // An invalid byte in the middle does not hold back the rest
+        feed(&fed, b"a\xffb\xe4");
+        assert_eq!(tail.read().unwrap().as_deref(), Some("a\u{FFFD}b"));
+
+        // A char never completed is decoded as it is when the program ends
+        tail.end();
+        assert_eq!(tail.read().unwrap().as_deref(), Some("\u{FFFD}"));
+    }
+
+    #[test]
+    fn test_read_consumed_input() {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let [read_end, write_end] = fds;
+        let fed: Arc<Fed> = Default::default();
+        *fed.fifo.lock().unwrap() = write_end;
+        let mut tail = stdin_tail(&fed);
+
+        let input = b"hello\nworld\n";
+        assert_eq!(
+            unsafe { libc::write(write_end, input.as_ptr() as *const _, input.len()) },
+            input.len() as isize
+        );
+        feed(&fed, input);
+        // Nothing has been read by the program yet
+        assert_eq!(tail.read().unwrap(), None);
+
+        let mut buffer = [0u8; 6];
+        assert_eq!(
+            unsafe { libc::read(read_end, buffer.as_mut_ptr() as *mut _, buffer.len()) },
+            6
+        );
+        assert_eq!(tail.read().unwrap().as_deref(), Some("hello\n"));
+        assert_eq!(tail.read().unwrap(), None);
+
+        // The program exits without reading the rest
+        tail.end();
+        assert_eq!(tail.read().unwrap(), None);
+
+        unsafe {
+            libc::close(read_end);
+            libc::close(write_end);
+        }
+    }
+}
@@ -126,3 +126,20 @@ mod test {
         assert!(!is_executable_fn("42.1: where = another_main`quick_sort::run_quick_sort + 24 at lib.rs:8:15, address = another_main[0x00000001000033d8], unresolved, hit count = 0"));
     }
 }
+
+/// CPU time of a thread in nanoseconds; 0 if not available on this platform
+pub(super) fn thread_cpu_time(pid: u64, tid: u64) -> u64 {
+    #[cfg(target_os = "linux")]
+    {
+        // The first field of `schedstat` is the time spent on the CPU in nanoseconds
+        std::fs::read_to_string(format!("/proc/{pid}/task/{tid}/schedstat"))
+            .ok()
+            .and_then(|stat| stat.split_whitespace().next()?.parse().ok())
+            .unwrap_or(0)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, tid);
+        0
+    }
+}
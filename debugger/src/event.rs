@@ -1,4 +1,4 @@
-use crate::{Bytes, Event, Reader};
+use crate::{Bytes, Event, ReadErr, Reader};
 
 #[derive(Debug)]
 /// Event Stream
@@ -15,34 +15,71 @@ pub struct ActiveFrame {
     pub program_counter: u64,
     pub function_name: String,
     pub function_id: lldb::SBFunctionId,
+    /// Arguments and return value are not captured
+    pub call_only: bool,
+    /// Whether this call is recorded, or skipped by sampling
+    pub recorded: bool,
+    /// If this is a poll of an `async fn`, the address of its future (the generator state)
+    pub future: Option<u64>,
+    /// Held back until the function returns, as its condition refers to the return value
+    pub deferred: Option<DeferredCall>,
+}
+
+#[cfg(feature = "debugger")]
+#[derive(Debug)]
+/// A call whose condition can only be evaluated when the function returns
+pub struct DeferredCall {
+    pub bp_id: crate::BpId,
+    /// The `FunctionCall` event
+    pub call: Bytes,
+    /// The events inside the call, streamed after the call if the condition is satisfied
+    pub events: Vec<Bytes>,
+}
+
+#[cfg(feature = "debugger")]
+#[derive(Debug, Default, Clone, Copy)]
+/// Timing of a function call or return
+pub struct Clock {
+    /// Nanoseconds the target has been running, excluding the time stopped by the debugger
+    pub clock: u64,
+    /// CPU time of the thread in nanoseconds; 0 if not recorded
+    pub cpu_time: u64,
 }
 
 impl EventStream {
-    pub fn read_from(source: Bytes) -> Event {
+    /// Like [`EventStream::read_from`], but returns an error instead of panicking on a malformed message,
+    /// e.g. one cut short by a crash of the debugger
+    pub fn try_read_from(source: Bytes) -> Result<Event, ReadErr> {
+        if source.len() == 0 {
+            return Err(ReadErr("Empty event".to_owned()));
+        }
         let mut reader = Reader::new();
-        assert!(source.len() > 0);
         let event = match source.get(0) {
             b'B' => {
                 let mut i = 1;
-                let reason = match source.get(i) {
-                    b'B' => crate::Reason::Breakpoint,
-                    b'P' => crate::Reason::Panic,
-                    b'F' => {
+                let reason = match source.as_bytes().get(i).copied() {
+                    Some(b'B') => crate::Reason::Breakpoint,
+                    Some(b'P') => crate::Reason::Panic,
+                    Some(b'T') => crate::Reason::Truncated,
+                    Some(b'W') => crate::Reason::Watch,
+                    Some(b'C') => crate::Reason::Catch,
+                    Some(b'F') => {
                         i += 1;
-                        match source.get(i) {
-                            b'{' => crate::Reason::FutureEnter,
-                            b'}' => crate::Reason::FutureExit,
-                            other => panic!("Unknown reason, got {other:?}"),
+                        match source.as_bytes().get(i).copied() {
+                            Some(b'{') => crate::Reason::FutureEnter,
+                            Some(b'~') => crate::Reason::FuturePoll,
+                            Some(b'}') => crate::Reason::FutureExit,
+                            other => return Err(ReadErr(format!("Unknown reason, got {other:?}"))),
                         }
                     }
-                    other => panic!("Unknown reason, got {other:?}"),
+                    other => return Err(ReadErr(format!("Unknown reason, got {other:?}"))),
                 };
                 i += 1;
                 reader.set_source(source, i);
-                let breakpoint_id = reader.read_int().unwrap() as u32;
-                let thread_id = reader.read_int().unwrap();
-                let frame_id = reader.read_int().unwrap();
-                let locals = reader.read_values();
+                let breakpoint_id = reader.read_int()? as u32;
+                let thread_id = reader.read_int()?;
+                let frame_id = reader.read_int()?;
+                let locals = reader.read_values()?;
                 Event::Breakpoint {
                     breakpoint_id,
                     thread_id,
@@ -53,12 +90,15 @@ impl EventStream {
             }
             b'F' => {
                 reader.set_source(source, 1);
-                let breakpoint_id = reader.read_int().unwrap() as u32;
-                let thread_id = reader.read_int().unwrap();
-                let frame_id = reader.read_int().unwrap();
-                let stack_pointer = reader.read_int().unwrap();
-                let function_name = reader.read_string().unwrap();
-                let arguments = reader.read_values();
+                let breakpoint_id = reader.read_int()? as u32;
+                let thread_id = reader.read_int()?;
+                let frame_id = reader.read_int()?;
+                let stack_pointer = reader.read_int()?;
+                // Recordings made before the timing was added have no clock
+                let clock = reader.read_int_if_any().unwrap_or_default();
+                let cpu_time = reader.read_int_if_any().unwrap_or_default();
+                let function_name = reader.read_string()?;
+                let arguments = reader.read_values()?;
                 Event::FunctionCall {
                     breakpoint_id,
                     thread_id,
@@ -66,28 +106,58 @@ impl EventStream {
                     stack_pointer,
                     function_name,
                     arguments,
+                    clock,
+                    cpu_time,
                 }
             }
             b'R' => {
                 reader.set_source(source, 1);
-                let breakpoint_id = reader.read_int().unwrap() as u32;
-                let thread_id = reader.read_int().unwrap();
-                let frame_id = reader.read_int().unwrap();
-                let function_name = reader.read_string().unwrap();
-                let mut values = reader.read_values();
-                let (name, return_value) = values.remove(0);
-                assert_eq!(name, "return_value");
+                let breakpoint_id = reader.read_int()? as u32;
+                let thread_id = reader.read_int()?;
+                let frame_id = reader.read_int()?;
+                let clock = reader.read_int_if_any().unwrap_or_default();
+                let cpu_time = reader.read_int_if_any().unwrap_or_default();
+                let function_name = reader.read_string()?;
+                let mut values = reader.read_values()?.into_iter();
+                let return_value = match values.next() {
+                    Some((name, return_value)) if name == "return_value" => return_value,
+                    other => return Err(ReadErr(format!("Expected return_value, got {other:?}"))),
+                };
                 Event::FunctionReturn {
                     breakpoint_id,
                     thread_id,
                     frame_id,
                     function_name,
                     return_value,
+                    clock,
+                    cpu_time,
+                }
+            }
+            b'U' => {
+                reader.set_source(source, 1);
+                let breakpoint_id = reader.read_int()? as u32;
+                let thread_id = reader.read_int()?;
+                let frame_id = reader.read_int()?;
+                let clock = reader.read_int()?;
+                let cpu_time = reader.read_int()?;
+                let function_name = reader.read_string()?;
+                Event::FunctionUnwind {
+                    breakpoint_id,
+                    thread_id,
+                    frame_id,
+                    function_name,
+                    clock,
+                    cpu_time,
                 }
             }
-            o => panic!("Unknown Event {o:?}"),
+            o => return Err(ReadErr(format!("Unknown Event {o:?}"))),
         };
-        event
+        Ok(event)
+    }
+
+    /// Panics on a malformed message; see [`EventStream::try_read_from`]
+    pub fn read_from(source: Bytes) -> Event {
+        Self::try_read_from(source).unwrap()
     }
 }
 
@@ -105,10 +175,15 @@ impl EventStream {
             crate::Reason::Breakpoint => b'B',
             crate::Reason::Panic => b'P',
             crate::Reason::FutureEnter => b'F',
+            crate::Reason::FuturePoll => b'F',
             crate::Reason::FutureExit => b'F',
+            crate::Reason::Truncated => b'T',
+            crate::Reason::Watch => b'W',
+            crate::Reason::Catch => b'C',
         });
         match reason {
             crate::Reason::FutureEnter => bytes.push_byte(b'{'),
+            crate::Reason::FuturePoll => bytes.push_byte(b'~'),
             crate::Reason::FutureExit => bytes.push_byte(b'}'),
             _ => (),
         }
@@ -118,13 +193,20 @@ impl EventStream {
         bytes
     }
 
-    pub fn function_call(bp_id: crate::BpId, thread_id: u64, active_frame: &ActiveFrame) -> Bytes {
+    pub fn function_call(
+        bp_id: crate::BpId,
+        thread_id: u64,
+        active_frame: &ActiveFrame,
+        clock: &Clock,
+    ) -> Bytes {
         let mut bytes = Bytes::new();
         bytes.push_byte(b'F');
         bytes.integer(bp_id.0);
         bytes.integer(thread_id);
         bytes.integer(active_frame.frame_id);
         bytes.integer(active_frame.stack_pointer);
+        bytes.integer(clock.clock);
+        bytes.integer(clock.cpu_time);
         bytes.identifier(&active_frame.function_name);
         bytes
     }
@@ -133,13 +215,150 @@ impl EventStream {
         bp_id: crate::BpId,
         thread_id: u64,
         active_frame: &ActiveFrame,
+        clock: &Clock,
     ) -> Bytes {
         let mut bytes = Bytes::new();
         bytes.push_byte(b'R');
         bytes.integer(bp_id.0);
         bytes.integer(thread_id);
         bytes.integer(active_frame.frame_id);
+        bytes.integer(clock.clock);
+        bytes.integer(clock.cpu_time);
         bytes.identifier(&active_frame.function_name);
         bytes
     }
+
+    pub fn function_unwind(
+        bp_id: crate::BpId,
+        thread_id: u64,
+        active_frame: &ActiveFrame,
+        clock: &Clock,
+    ) -> Bytes {
+        let mut bytes = Bytes::new();
+        bytes.push_byte(b'U');
+        bytes.integer(bp_id.0);
+        bytes.integer(thread_id);
+        bytes.integer(active_frame.frame_id);
+        bytes.integer(clock.clock);
+        bytes.integer(clock.cpu_time);
+        bytes.identifier(&active_frame.function_name);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{PValue, RValue};
+
+    fn bytes(s: &str) -> Bytes {
+        let mut bytes = Bytes::new();
+        bytes.push_str(s);
+        bytes
+    }
+
+    /// Value of the `u64` 5 named `name`
+    fn u64_value(bytes: &mut Bytes, name: &str) {
+        bytes.push_str(&format!("\" {name}\"name \" u64\"# "));
+        bytes.push_slice(&8u32.to_ne_bytes());
+        bytes.push_slice(&5u64.to_ne_bytes());
+        bytes.push_str(" prim ");
+    }
+
+    #[test]
+    fn test_read_function_call() {
+        let mut source = bytes("F1 2 3 4 100 20 \" main\"");
+        u64_value(&mut source, "i");
+        assert_eq!(
+            EventStream::read_from(source),
+            Event::FunctionCall {
+                breakpoint_id: 1,
+                thread_id: 2,
+                frame_id: 3,
+                stack_pointer: 4,
+                function_name: "main".to_owned(),
+                arguments: vec![("i".to_owned(), RValue::Prim(PValue::u64(5)))],
+                clock: 100,
+                cpu_time: 20,
+            }
+        );
+
+        // Before the timing was added
+        let mut source = bytes("F1 2 3 4 \" main\"");
+        u64_value(&mut source, "i");
+        assert_eq!(
+            EventStream::read_from(source),
+            Event::FunctionCall {
+                breakpoint_id: 1,
+                thread_id: 2,
+                frame_id: 3,
+                stack_pointer: 4,
+                function_name: "main".to_owned(),
+                arguments: vec![("i".to_owned(), RValue::Prim(PValue::u64(5)))],
+                clock: 0,
+                cpu_time: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_function_return() {
+        let mut source = bytes("R1 2 3 100 20 \" main\"");
+        u64_value(&mut source, "return_value");
+        assert_eq!(
+            EventStream::read_from(source),
+            Event::FunctionReturn {
+                breakpoint_id: 1,
+                thread_id: 2,
+                frame_id: 3,
+                function_name: "main".to_owned(),
+                return_value: RValue::Prim(PValue::u64(5)),
+                clock: 100,
+                cpu_time: 20,
+            }
+        );
+
+        // Before the timing was added
+        let mut source = bytes("R1 2 3 \" main\"");
+        u64_value(&mut source, "return_value");
+        assert_eq!(
+            EventStream::read_from(source),
+            Event::FunctionReturn {
+                breakpoint_id: 1,
+                thread_id: 2,
+                frame_id: 3,
+                function_name: "main".to_owned(),
+                return_value: RValue::Prim(PValue::u64(5)),
+                clock: 0,
+                cpu_time: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_truncated() {
+        let mut source = bytes("F1 2 3 4 100 20 \" main\"");
+        u64_value(&mut source, "i");
+        let full = source.into_bytes();
+        let truncated = |len: usize| {
+            let mut source = Bytes::new();
+            source.push_slice(&full[..len]);
+            EventStream::try_read_from(source)
+        };
+        // Never panics, wherever it is cut
+        for len in 0..full.len() {
+            let _ = truncated(len);
+        }
+        assert!(truncated(full.len()).is_ok());
+        assert!(truncated(0).is_err());
+        // In the middle of an integer, the function name and the bytes of the argument
+        assert!(truncated(10).is_err());
+        assert!(truncated(19).is_err());
+        assert!(truncated(full.len() - 10).is_err());
+        // In the middle of the last op
+        assert!(truncated(full.len() - 2).is_err());
+
+        assert!(EventStream::try_read_from(bytes("X1 2 3 ")).is_err());
+        assert!(EventStream::try_read_from(bytes("BX1 2 3 ")).is_err());
+    }
 }
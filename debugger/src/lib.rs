@@ -14,6 +14,9 @@
 //! | `RECURSIVE_DEREF_LIMIT` | `usize` | Recursive limit; i.e. this limits the depth of a binary tree |
 //! | `KEEP_HASH_ORDER` | `bool` | If set, don't sort hash maps by hash key |
 //! | `DONT_TRACE_ALLOCATION` | `bool` | If set, don't trace heap allocations |
+//! | `SAMPLE` | `String` | If set, only record a sample of function calls; `1/100` records every 100th call of each function, `first:50` records only the first 50 calls of each function |
+//! | `MAX_HITS_PER_FUNCTION` | `usize` | If set, stop tracing a function after it has been called this many times |
+//! | `RECORD_CPU_TIME` | `bool` | If set, record the CPU time of the thread on each function call and return (Linux only) |
 //!
 //! ### Instruction Set
 //!
@@ -84,8 +87,27 @@
 //!
 //! ### Heap allocation
 //!
-//! Right now it is still a WIP. We can trace all `Box`, `Rc`, `Arc` allocations now, so that we are able to extract the content of `Box<dyn T>`.
-//! The TODO is to trace deallocations and output the information to a dedicated event stream.
+//! We trace all `Box`, `Rc`, `Arc` allocations, so that we are able to extract the content of `Box<dyn T>`.
+//!
+//! In addition, we break on the allocator shims `__rust_alloc`, `__rust_realloc` and `__rust_dealloc` (which forward to the `#[global_allocator]`, if any), so growths of `Vec`, `String`, `HashMap` etc are visible too.
+//! Each allocation is streamed to the `allocation` stream with its size, alignment and the innermost recorded frame. Allocations made outside of any recorded frame (e.g. by the runtime) are ignored, unless they are the ones of `Box`, `Rc`, `Arc`.
+//!
+//! ### Program output
+//!
+//! The stdout and stderr of the program are redirected to `<basename>.stdout` and `<basename>.stderr`, which are tailed to the terminal.
+//! On every stop, the output written since the last stop is streamed to the `output` stream, along with the clock, the stopped thread and its innermost recorded frame; so a log line can be lined up with the function calls active when it was printed.
+//!
+//! ### Program exit
+//!
+//! When the program is terminated by a signal, e.g. `SIGSEGV` from an FFI bug or `SIGABRT` from a double panic, the `Exit` info message carries the signal, the exit description and a backtrace of every thread at the time of the crash; they end up in the `exit_*` columns of the `debugger_info` table.
+//!
+//! ### Panic unwinding
+//!
+//! A panic unwinds through the traced frames without returning from them. When `catch_unwind` catches it (the standard library catches the panics of `main` and of every spawned thread too), a `FunctionUnwind` event is emitted for each frame it unwound through, followed by a `Catch` breakpoint event on the frame which called `catch_unwind`, so the call tree stays well-formed after a recovered panic.
+//!
+//! ### Async functions
+//!
+//! The breakpoint of an `async fn` is hit both when it is called, which only creates the future, and every time the future is polled, i.e. its generator `{{closure}}` is resumed. A breakpoint at its closing brace tells when the future is created (`FutureEnter`) and when it completes (`FutureExit`). Each poll is followed by a `FuturePoll` event carrying the address of the future (the generator state, which is pinned and stable across polls) and the poll count; the return value of the poll which hit `FutureExit` is the `Poll::Ready` result.
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![deny(
     missing_debug_implementations,
@@ -94,6 +116,7 @@
 )]
 
 mod bytes;
+mod condition;
 #[cfg(feature = "debugger")]
 mod debugger;
 mod event;
@@ -104,6 +127,7 @@ mod value;
 pub mod version;
 
 pub use bytes::*;
+pub use condition::*;
 #[cfg(feature = "debugger")]
 pub use debugger::*;
 pub use event::*;
@@ -111,4 +135,6 @@ pub use reader::*;
 use rvalue::*;
 use value::*;
 
-pub use firedbg_protocol::{allocation::*, breakpoint::*, event::*, info::*, source::*, value::*};
+pub use firedbg_protocol::{
+    allocation::*, breakpoint::*, event::*, info::*, output::*, panic::*, source::*, value::*,
+};
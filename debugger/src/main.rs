@@ -1,19 +1,24 @@
 use anyhow::{Context, Result};
 use firedbg_rust_debugger::{
-    check_rustc_version, get_target_basename, new_breakpoint, Debugger, DebuggerInfo,
-    DebuggerParams, FireDbgForRust, InfoMessage, SourceFile, INFO_STREAM,
+    check_rustc_version, get_target_basename, new_async_breakpoint, new_breakpoint,
+    new_call_only_breakpoint, new_statement_breakpoint, new_watch_breakpoint, Condition, Debugger,
+    DebuggerInfo, DebuggerParams, FireDbgForRust, InfoMessage, RecordingWindow, SourceFile,
+    StdinSource, INFO_STREAM,
 };
-use firedbg_rust_parser::{serde::from_bson_file, File};
-use glob::glob;
+use firedbg_rust_parser::{serde::from_bson_file, File, FunctionDef, FunctionType};
+use glob::{glob, Pattern};
 use sea_streamer::{
     export::futures::{select, FutureExt},
     file::{FileId, FileSource, ReadFrom},
-    runtime::spawn_task,
+    runtime::{spawn_task, TaskHandle},
     Producer, SeaConnectOptions, SeaProducer, SeaStreamer, StreamKey, Streamer,
 };
 use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::IsTerminal,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 use structopt::StructOpt;
 use tokio::{fs::create_dir_all, sync::Notify};
@@ -52,6 +57,34 @@ struct Command {
     /// Package configurations
     #[structopt(long = "package", global = true, parse(try_from_str = parse_package_cfg))]
     package_cfgs: Vec<PackageCfg>,
+    /// Watch expressions
+    #[structopt(long = "watch", global = true, parse(try_from_str = parse_watch_cfg))]
+    watch_cfgs: Vec<WatchCfg>,
+    /// Conditions of function breakpoints and `fire::dbg!`
+    #[structopt(long = "condition", global = true, parse(try_from_str = parse_condition_cfg))]
+    condition_cfgs: Vec<ConditionCfg>,
+    /// Start recording after the program has been running for this many seconds
+    #[structopt(long, global = true, parse(try_from_str = parse_secs))]
+    record_after: Option<Duration>,
+    /// Stop recording after recording for this many seconds
+    #[structopt(long, global = true, parse(try_from_str = parse_secs))]
+    record_for: Option<Duration>,
+    /// Start / stop recording whenever the program receives `SIGUSR1`
+    #[structopt(long, global = true)]
+    record_on_signal: bool,
+    /// Feed the content of this file to the program as stdin, or pass through the stdin with `-`.
+    /// By default, the stdin is passed through if it is not a terminal.
+    #[structopt(long, global = true)]
+    stdin: Option<String>,
+    /// Set an environment variable of the program
+    #[structopt(long = "env", global = true, parse(try_from_str = parse_env))]
+    env: Vec<(String, String)>,
+    /// Remove an environment variable inherited by the program
+    #[structopt(long, global = true)]
+    env_remove: Vec<String>,
+    /// Working directory of the program
+    #[structopt(long, global = true)]
+    cwd: Option<String>,
     #[structopt(subcommand)]
     sub_command: SubCommand,
 }
@@ -80,22 +113,202 @@ enum SubCommand {
         example_executable: String,
         args: Vec<String>,
     },
+    /// Attach to a running process of a binary with `firedbg` debugging enabled; detach on Ctrl-C
+    Attach { binary_executable: String, pid: u64 },
 }
 
 #[derive(Debug)]
 struct PackageCfg {
     package: String,
     trace: String,
+    /// Only trace functions matching any of these globs
+    include: Vec<Pattern>,
+    /// Don't trace functions matching any of these globs
+    exclude: Vec<Pattern>,
 }
 
+/// Syntax: `<PACKAGE_PATH>/<TRACE>[;include=<GLOB>][;exclude=<GLOB>]`
 fn parse_package_cfg(src: &str) -> Result<PackageCfg> {
-    let (package, trace) = src.rsplit_once('/').expect("delimiter");
-    Ok(PackageCfg {
+    let mut parts = src.split(';');
+    let (package, trace) = parts
+        .next()
+        .expect("At least one")
+        .rsplit_once('/')
+        .expect("delimiter");
+    let mut package_cfg = PackageCfg {
         package: package.into(),
         trace: trace.into(),
+        include: Vec::new(),
+        exclude: Vec::new(),
+    };
+    for part in parts {
+        let (key, glob) = part
+            .split_once('=')
+            .with_context(|| format!("Invalid package filter: `{part}`"))?;
+        let pattern =
+            Pattern::new(glob).with_context(|| format!("Invalid glob pattern: `{glob}`"))?;
+        match key {
+            "include" => package_cfg.include.push(pattern),
+            "exclude" => package_cfg.exclude.push(pattern),
+            _ => anyhow::bail!("Unknown package filter: `{key}`"),
+        }
+    }
+    Ok(package_cfg)
+}
+
+fn parse_secs(src: &str) -> Result<Duration> {
+    let secs: f64 = src
+        .parse()
+        .with_context(|| format!("Invalid seconds: `{src}`"))?;
+    Duration::try_from_secs_f64(secs).with_context(|| format!("Invalid seconds: `{src}`"))
+}
+
+/// Syntax: `<KEY>=<VALUE>`
+fn parse_env(src: &str) -> Result<(String, String)> {
+    let (key, value) = src
+        .split_once('=')
+        .with_context(|| format!("Invalid environment variable: `{src}`"))?;
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+#[derive(Debug)]
+struct WatchCfg {
+    file: String,
+    line: u32,
+    vars: Vec<String>,
+    when: Option<Condition>,
+}
+
+/// Syntax: `<FILE>:<LINE>:<VAR>[,<VAR>][;when=<CONDITION>]`
+fn parse_watch_cfg(src: &str) -> Result<WatchCfg> {
+    let (src, when) = split_condition(src)?;
+    let mut parts = src.rsplitn(3, ':');
+    let (Some(vars), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+        anyhow::bail!("Invalid watch: `{src}`");
+    };
+    Ok(WatchCfg {
+        file: file.into(),
+        line: line
+            .parse()
+            .with_context(|| format!("Invalid line number: `{line}`"))?,
+        vars: vars.split(',').map(Into::into).collect(),
+        when,
     })
 }
 
+#[derive(Debug)]
+enum ConditionCfg {
+    /// Functions matching the glob, like the include / exclude globs
+    Function(Pattern, Condition),
+    /// `fire::dbg!` of the expression
+    Dbg(String, Condition),
+}
+
+/// Syntax: `function=<GLOB>;when=<CONDITION>` or `dbg=<EXPR>;when=<CONDITION>`
+fn parse_condition_cfg(src: &str) -> Result<ConditionCfg> {
+    let (target, when) = split_condition(src)?;
+    let when = when.with_context(|| format!("Missing `when` in condition: `{src}`"))?;
+    match target.split_once('=') {
+        Some(("function", glob)) => Ok(ConditionCfg::Function(
+            Pattern::new(glob).with_context(|| format!("Invalid glob pattern: `{glob}`"))?,
+            when,
+        )),
+        Some(("dbg", expr)) => Ok(ConditionCfg::Dbg(expr.into(), when)),
+        _ => anyhow::bail!("Invalid condition: `{src}`"),
+    }
+}
+
+/// Split the trailing `;when=<CONDITION>`
+fn split_condition(src: &str) -> Result<(&str, Option<Condition>)> {
+    match src.split_once(";when=") {
+        Some((src, when)) => {
+            let condition = when
+                .parse()
+                .map_err(|e| anyhow::anyhow!("{e}"))
+                .with_context(|| format!("Invalid condition: `{when}`"))?;
+            Ok((src, Some(condition)))
+        }
+        None => Ok((src, None)),
+    }
+}
+
+impl PackageCfg {
+    /// Whether we should set breakpoint on the function, with respect to the include / exclude globs
+    fn is_traced(&self, module_path: &str, func: &FunctionDef) -> bool {
+        let names = function_names(module_path, &func.ty);
+        let matches = |patterns: &[Pattern]| {
+            patterns
+                .iter()
+                .any(|pattern| names.iter().any(|name| pattern.matches(name)))
+        };
+        (self.include.is_empty() || matches(&self.include)) && !matches(&self.exclude)
+    }
+}
+
+/// Module path of a source file relative to the source directory, e.g. `utils/fmt.rs` -> `utils::fmt`
+fn module_path(rel_path: &Path) -> String {
+    let mut segments: Vec<_> = rel_path
+        .with_extension("")
+        .iter()
+        .map(|segment| segment.to_string_lossy().into_owned())
+        .collect();
+    match segments.last().map(String::as_str) {
+        Some("mod") => {
+            segments.pop();
+        }
+        Some("lib" | "main") if segments.len() == 1 => {
+            segments.pop();
+        }
+        _ => (),
+    }
+    segments.join("::")
+}
+
+/// Names of a function to be matched against the include / exclude globs:
+/// function name, module path, self type, trait name and the qualified path, e.g. `utils::Cat::fmt`
+fn function_names(module_path: &str, ty: &FunctionType) -> Vec<String> {
+    let (self_type, trait_name) = match ty {
+        FunctionType::ImplFn { self_type, .. } => (Some(self_type), None),
+        FunctionType::ImplTraitFn {
+            self_type,
+            trait_name,
+            ..
+        } => (Some(self_type), Some(trait_name)),
+        FunctionType::TraitDefaultFn { trait_name, .. } => (None, Some(trait_name)),
+        FunctionType::FreeFn { .. } | FunctionType::NestedFn { .. } => (None, None),
+    };
+    // Type names are token streams, e.g. `fmt :: Display`
+    let self_type = self_type.map(|s| s.replace(' ', ""));
+    let trait_name = trait_name.map(|s| s.replace(' ', ""));
+
+    let fn_name = ty.fn_name();
+    let mut names = vec![fn_name.to_owned()];
+    if !module_path.is_empty() {
+        names.push(module_path.to_owned());
+    }
+    if let Some(self_type) = &self_type {
+        names.push(self_type.to_owned());
+    }
+    if let Some(trait_name) = &trait_name {
+        names.push(trait_name.to_owned());
+        // `fmt::Display` is also matched by `Display`
+        if let Some((_, name)) = trait_name.rsplit_once("::") {
+            names.push(name.to_owned());
+        }
+    }
+    let qualified = [
+        module_path,
+        self_type.as_deref().or(trait_name.as_deref()).unwrap_or(""),
+        fn_name,
+    ]
+    .into_iter()
+    .filter(|s| !s.is_empty())
+    .collect::<Vec<_>>()
+    .join("::");
+    names.push(qualified);
+    names
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     env_logger::init();
@@ -105,6 +318,15 @@ async fn main() -> Result<()> {
     let Command {
         workspace_root,
         package_cfgs,
+        watch_cfgs,
+        condition_cfgs,
+        record_after,
+        record_for,
+        record_on_signal,
+        stdin,
+        env,
+        env_remove,
+        cwd,
         output,
         package_name,
         sub_command,
@@ -117,6 +339,19 @@ async fn main() -> Result<()> {
 
     let mut files = vec![Default::default()];
     let mut breakpoints = vec![Default::default()];
+    let mut conditions = HashMap::new();
+    let mut dbg_conditions = HashMap::new();
+    let mut function_conditions = Vec::new();
+    for condition_cfg in condition_cfgs {
+        match condition_cfg {
+            ConditionCfg::Function(pattern, condition) => {
+                function_conditions.push((pattern, condition))
+            }
+            ConditionCfg::Dbg(expr, condition) => {
+                dbg_conditions.insert(expr, condition);
+            }
+        }
+    }
     let mut id = 1;
 
     let get_map_file = |src_file: &PathBuf| {
@@ -124,10 +359,11 @@ async fn main() -> Result<()> {
         format!("{workspace_root}/firedbg/{src_rel_path}.firedbg.map")
     };
 
-    let mut set_file_breakpoint = |file: File| {
+    let mut set_file_breakpoint = |file: File, package_cfg: &PackageCfg, module_path: &str| {
         let File {
             path,
             functions,
+            statements,
             crate_name,
             modified,
         } = file;
@@ -143,14 +379,62 @@ async fn main() -> Result<()> {
             crate_name,
             modified,
         });
-        for func in functions {
-            breakpoints.push(new_breakpoint(breakpoints.len() as u32, id, &func));
+        let call_only = package_cfg.trace == "call-only";
+        for func in functions.iter() {
+            if !package_cfg.is_traced(module_path, func) {
+                continue;
+            }
+            let new_breakpoint = if call_only {
+                new_call_only_breakpoint
+            } else {
+                new_breakpoint
+            };
+            let bp_id = breakpoints.len() as u32;
+            breakpoints.push(new_breakpoint(bp_id, id, func));
+            if func.ty.is_async() && !call_only {
+                // Break at the closing brace to tell when the future is created and when it completes
+                breakpoints.push(new_async_breakpoint(breakpoints.len() as u32, id, func));
+            }
+            let names = function_names(module_path, &func.ty);
+            if let Some((_, condition)) = function_conditions
+                .iter()
+                .find(|(pattern, _)| names.iter().any(|name| pattern.matches(name)))
+            {
+                conditions.insert(bp_id, condition.clone());
+            }
+        }
+        if package_cfg.trace == "statements" {
+            // Break at each line with statements, but not the first and last line of a function,
+            // which are shared with the function call and future endpoint breakpoints
+            let mut lines = BTreeSet::new();
+            for stmt in statements.iter() {
+                let line = stmt.loc().line;
+                // The innermost function enclosing this statement
+                let func = functions
+                    .iter()
+                    .filter(|func| func.loc.start.line <= line && line <= func.end.line)
+                    .max_by_key(|func| func.loc.start.line);
+                match func {
+                    Some(func)
+                        if func.loc.end.line < line
+                            && line < func.end.line
+                            && package_cfg.is_traced(module_path, func) =>
+                    {
+                        lines.insert(line);
+                    }
+                    _ => (),
+                }
+            }
+            for line in lines {
+                breakpoints.push(new_statement_breakpoint(breakpoints.len() as u32, id, line));
+            }
         }
         id += 1;
     };
 
     let get_arguments = |testcase: String| vec!["--exact".into(), testcase];
 
+    let mut pid = None;
     let (binary, src_dirs, arguments) = match sub_command {
         SubCommand::Run {
             binary_executable,
@@ -178,14 +462,25 @@ async fn main() -> Result<()> {
             example_executable,
             args,
         } => (example_executable, ["src", "examples"], args),
+        SubCommand::Attach {
+            binary_executable,
+            pid: process_id,
+        } => {
+            pid = Some(process_id);
+            (binary_executable, ["src", "bin"], Vec::new())
+        }
     };
 
-    for PackageCfg { package, .. } in package_cfgs
-        .iter()
-        .filter(|package_cfg| matches!(package_cfg.trace.as_str(), "full" | "call-only"))
-    {
+    for package_cfg in package_cfgs.iter().filter(|package_cfg| {
+        matches!(
+            package_cfg.trace.as_str(),
+            "full" | "call-only" | "statements"
+        )
+    }) {
+        let package = &package_cfg.package;
         for src_dir in src_dirs.iter() {
-            let src_regex = &format!("{workspace_root}/{package}/{src_dir}/**/*.rs");
+            let src_root = format!("{workspace_root}/{package}/{src_dir}");
+            let src_regex = &format!("{src_root}/**/*.rs");
             log::debug!("src_regex `{}`", src_regex);
             let context = || format!("Invalid glob regex: `{src_regex}`");
             for src_file in glob(src_regex)
@@ -197,8 +492,62 @@ async fn main() -> Result<()> {
                 let file = from_bson_file(&map_file)
                     .await
                     .with_context(|| format!("Fail to deserialize BSON file: `{map_file}`"))?;
-                set_file_breakpoint(file)
+                let module_path = src_file
+                    .strip_prefix(&src_root)
+                    .map(module_path)
+                    .unwrap_or_default();
+                set_file_breakpoint(file, package_cfg, &module_path)
+            }
+        }
+    }
+
+    // Multiple watches on the same line share one breakpoint
+    // (vars, condition); the breakpoint is recorded if any of the conditions is satisfied
+    let mut watches: BTreeMap<(&str, u32), (Vec<String>, Option<Option<Condition>>)> =
+        BTreeMap::new();
+    for WatchCfg {
+        file,
+        line,
+        vars,
+        when,
+    } in watch_cfgs.iter()
+    {
+        let (watch_vars, watch_when) = watches.entry((file.as_str(), *line)).or_default();
+        for var in vars {
+            if !watch_vars.contains(var) {
+                watch_vars.push(var.to_owned());
+            }
+        }
+        *watch_when = Some(match (watch_when.take(), when.clone()) {
+            (None, when) => when,
+            (Some(Some(a)), Some(b)) => Some(a.or(b)),
+            // Unconditional
+            (Some(_), _) => None,
+        });
+    }
+    for ((path, line), (vars, when)) in watches {
+        let file_id = match files.iter().position(|file| file.path == path) {
+            Some(file_id) => file_id as u32,
+            None => {
+                // The file is not traced, but we can still break in it
+                let src_file = format!("{workspace_root}/{path}");
+                let modified = std::fs::metadata(&src_file)
+                    .and_then(|metadata| metadata.modified())
+                    .with_context(|| format!("Fail to read file metadata: `{src_file}`"))?;
+                let file_id = files.len() as u32;
+                files.push(SourceFile {
+                    id: file_id,
+                    path: path.to_owned(),
+                    crate_name: Default::default(),
+                    modified,
+                });
+                file_id
             }
+        };
+        let bp_id = breakpoints.len() as u32;
+        breakpoints.push(new_watch_breakpoint(bp_id, file_id, line, vars));
+        if let Some(Some(condition)) = when {
+            conditions.insert(bp_id, condition);
         }
     }
 
@@ -212,6 +561,9 @@ async fn main() -> Result<()> {
             package_name,
             target: binary.clone(),
             arguments: arguments.clone(),
+            env: env.iter().cloned().collect(),
+            env_remove: env_remove.clone(),
+            cwd: cwd.clone(),
         }))
         .context("Fail to serialize")?
         .as_str(),
@@ -222,20 +574,68 @@ async fn main() -> Result<()> {
         files,
         breakpoints,
         arguments,
+        conditions,
+        dbg_conditions,
+        pid,
+        recording: RecordingWindow {
+            after: record_after,
+            length: record_for,
+            on_signal: record_on_signal,
+        },
+        stdin: match stdin {
+            Some(path) if path == "-" => StdinSource::Inherit,
+            Some(path) => StdinSource::File(path),
+            // Only read the terminal when asked to, or a backgrounded debugger would be stopped
+            None if std::io::stdin().is_terminal() => StdinSource::None,
+            None => StdinSource::Inherit,
+        },
+        env: env.into_iter().collect(),
+        env_remove,
+        cwd,
     };
 
+    if pid.is_some() {
+        // Detach on Ctrl-C, leaving the process running
+        spawn_task(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                Debugger::detach();
+            }
+        });
+    }
+
+    // Tail program stdout and stderr
+    let target_basename = get_target_basename(&output);
+    let (stdout_handle, stdout_notifier) = tail_output(format!("{target_basename}.stdout"), false)?;
+    let (stderr_handle, stderr_notifier) = tail_output(format!("{target_basename}.stderr"), true)?;
+
+    // Run the debugger
+    Debugger::run(debugger_params, producer.clone());
+
+    // Cleanup and kill the tail tasks
+    producer.end().await.context("Fail to kill producer")?;
+    stdout_notifier.notify_one();
+    stderr_notifier.notify_one();
+    stdout_handle.await??;
+    stderr_handle.await??;
+
+    Ok(())
+}
+
+/// Print everything appended to the file, until notified
+fn tail_output(path: String, to_stderr: bool) -> Result<(TaskHandle<Result<()>>, Arc<Notify>)> {
+    // We need to create an empty file to be able to tail it,
+    // the program output will be appended to the file
+    std::fs::File::create(&path).with_context(|| format!("Fail to create file: `{path}`"))?;
     let notify = Arc::new(Notify::new());
     let notifier = notify.clone();
+    let print = if to_stderr {
+        print_to_stderr
+    } else {
+        print_to_stdout
+    };
 
-    // Tail program stdout
-    let tail_handle = spawn_task::<_, Result<()>>(async move {
-        // We need to create an empty file to be able to tail it,
-        // stdout messages will be appended to the file
-        let target_basename = get_target_basename(&output);
-        let path = format!("{target_basename}.stdout");
-        std::fs::File::create(&path).with_context(|| format!("Fail to create file: `{path}`"))?;
+    let handle = spawn_task::<_, Result<()>>(async move {
         let file_id = FileId::new(path);
-
         let mut source = FileSource::new(file_id.clone(), ReadFrom::Beginning)
             .await
             .context("Fail to start file source")?;
@@ -243,7 +643,7 @@ async fn main() -> Result<()> {
         loop {
             select! {
                 res = FileSource::stream_bytes(&mut source).fuse() => {
-                    print_to_stdout(res.context("read stdout")?)?;
+                    print(res.with_context(|| format!("read {}", file_id.path()))?)?;
                 }
                 _ = notify.notified().fuse() => {
                     break;
@@ -253,20 +653,12 @@ async fn main() -> Result<()> {
         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         let mut buffer = source.drain().await;
         if !buffer.is_empty() {
-            print_to_stdout(buffer.consume(buffer.size()))?;
+            print(buffer.consume(buffer.size()))?;
         }
         Ok(())
     });
 
-    // Run the debugger
-    Debugger::run(debugger_params, producer.clone());
-
-    // Cleanup and kill the tail task
-    producer.end().await.context("Fail to kill producer")?;
-    notifier.notify_one();
-    tail_handle.await??;
-
-    Ok(())
+    Ok((handle, notifier))
 }
 
 pub async fn create_streamer(output: &str) -> Result<SeaProducer> {
@@ -300,3 +692,181 @@ fn print_to_stdout(bytes: sea_streamer::file::Bytes) -> Result<()> {
     );
     std::io::Write::flush(&mut std::io::stdout()).context("flush")
 }
+
+fn print_to_stderr(bytes: sea_streamer::file::Bytes) -> Result<()> {
+    eprint!(
+        "{}",
+        std::str::from_utf8(&bytes.bytes()).context("read utf8")?
+    );
+    std::io::Write::flush(&mut std::io::stderr()).context("flush")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use firedbg_rust_debugger::LineColumn;
+    use firedbg_rust_parser::BreakableSpan;
+
+    fn func(ty: FunctionType) -> FunctionDef {
+        let at = LineColumn {
+            line: 1,
+            column: Some(1),
+        };
+        FunctionDef {
+            ty,
+            loc: BreakableSpan {
+                start: at.clone(),
+                end: at.clone(),
+            },
+            end: at,
+        }
+    }
+
+    fn free_fn(fn_name: &str) -> FunctionDef {
+        func(FunctionType::FreeFn {
+            fn_name: fn_name.into(),
+            is_async: false,
+            return_type: false,
+        })
+    }
+
+    fn impl_trait_fn(trait_name: &str, self_type: &str, fn_name: &str) -> FunctionDef {
+        func(FunctionType::ImplTraitFn {
+            trait_name: trait_name.into(),
+            self_type: self_type.into(),
+            fn_name: fn_name.into(),
+            is_async: false,
+            is_static: false,
+            return_type: true,
+        })
+    }
+
+    #[test]
+    fn test_parse_package_cfg() -> Result<()> {
+        let cfg = parse_package_cfg("/home/user/quicksort/full")?;
+        assert_eq!(cfg.package, "/home/user/quicksort");
+        assert_eq!(cfg.trace, "full");
+        assert!(cfg.include.is_empty());
+        assert!(cfg.exclude.is_empty());
+
+        let cfg = parse_package_cfg(
+            "/home/user/quicksort/full;include=utils::*;exclude=*::fmt;include=main",
+        )?;
+        assert_eq!(cfg.package, "/home/user/quicksort");
+        assert_eq!(cfg.trace, "full");
+        assert_eq!(
+            cfg.include.iter().map(Pattern::as_str).collect::<Vec<_>>(),
+            ["utils::*", "main"]
+        );
+        assert_eq!(
+            cfg.exclude.iter().map(Pattern::as_str).collect::<Vec<_>>(),
+            ["*::fmt"]
+        );
+
+        assert!(parse_package_cfg("/home/user/quicksort/full;include").is_err());
+        assert!(parse_package_cfg("/home/user/quicksort/full;only=main").is_err());
+        assert!(parse_package_cfg("/home/user/quicksort/full;include=[").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path(Path::new("lib.rs")), "");
+        assert_eq!(module_path(Path::new("main.rs")), "");
+        assert_eq!(module_path(Path::new("utils.rs")), "utils");
+        assert_eq!(module_path(Path::new("utils/mod.rs")), "utils");
+        assert_eq!(module_path(Path::new("utils/fmt.rs")), "utils::fmt");
+        assert_eq!(module_path(Path::new("bin/main.rs")), "bin::main");
+    }
+
+    #[test]
+    fn test_function_names() {
+        assert_eq!(function_names("", &free_fn("main").ty), ["main", "main"]);
+        assert_eq!(
+            function_names("utils", &free_fn("sort").ty),
+            ["sort", "utils", "utils::sort"]
+        );
+        assert_eq!(
+            function_names(
+                "utils",
+                &func(FunctionType::ImplFn {
+                    self_type: "Cat".into(),
+                    fn_name: "new".into(),
+                    is_async: false,
+                    is_static: true,
+                    return_type: true,
+                })
+                .ty
+            ),
+            ["new", "utils", "Cat", "utils::Cat::new"]
+        );
+        assert_eq!(
+            function_names("utils", &impl_trait_fn("fmt :: Display", "Cat", "fmt").ty),
+            [
+                "fmt",
+                "utils",
+                "Cat",
+                "fmt::Display",
+                "Display",
+                "utils::Cat::fmt"
+            ]
+        );
+        assert_eq!(
+            function_names(
+                "",
+                &func(FunctionType::TraitDefaultFn {
+                    trait_name: "Animal".into(),
+                    fn_name: "speak".into(),
+                    is_async: false,
+                    is_static: false,
+                    return_type: false,
+                })
+                .ty
+            ),
+            ["speak", "Animal", "Animal::speak"]
+        );
+    }
+
+    #[test]
+    fn test_is_traced() -> Result<()> {
+        let main = free_fn("main");
+        let sort = free_fn("sort");
+        let fmt = impl_trait_fn("fmt :: Display", "Cat", "fmt");
+
+        let traced = |cfg: &str| -> Result<_> {
+            let cfg = parse_package_cfg(cfg)?;
+            Ok([
+                cfg.is_traced("", &main),
+                cfg.is_traced("utils", &sort),
+                cfg.is_traced("utils", &fmt),
+            ])
+        };
+
+        // No filters: everything
+        assert_eq!(traced("/quicksort/full")?, [true, true, true]);
+        // Include only
+        assert_eq!(
+            traced("/quicksort/full;include=utils")?,
+            [false, true, true]
+        );
+        assert_eq!(
+            traced("/quicksort/full;include=main;include=Cat")?,
+            [true, false, true]
+        );
+        // Exclude only
+        assert_eq!(
+            traced("/quicksort/full;exclude=Display")?,
+            [true, true, false]
+        );
+        assert_eq!(
+            traced("/quicksort/full;exclude=*::fmt")?,
+            [true, true, false]
+        );
+        // Exclude wins over include
+        assert_eq!(
+            traced("/quicksort/full;include=utils::*;exclude=Cat")?,
+            [false, true, false]
+        );
+        Ok(())
+    }
+}
@@ -1,9 +1,22 @@
 use crate::{
-    Addr, ArrayType, Bytes, PValue, RVal, RValue, RValueLift, RefAddr, StringType, UnionType, Val,
+    Addr, ArrayType, Bytes, PValue, RVal, RValue, RValueLift, RefAddr, RefType, StringType,
+    UnionType, Val,
 };
 use firedbg_protocol::IndexMap;
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The stream is malformed, e.g. a message cut short by a crash of the debugger
+pub struct ReadErr(pub String);
+
+impl std::fmt::Display for ReadErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Read Value Error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ReadErr {}
+
 #[derive(Debug)]
 /// Stream Reader
 pub struct Reader {
@@ -38,23 +51,33 @@ impl Reader {
         }
     }
 
-    pub fn read_values(&mut self) -> Vec<(String, RValue)> {
+    pub fn read_values(&mut self) -> Result<Vec<(String, RValue)>, ReadErr> {
         self.source.read_values(&mut self.context)
     }
 
-    pub fn read_string(&mut self) -> Option<String> {
-        match self.source.next_token() {
-            Some(Token::Str(string)) => Some(string),
-            Some(other) => panic!("Expected String, got {other:?}"),
-            None => None,
+    pub fn read_string(&mut self) -> Result<String, ReadErr> {
+        match self.source.next_token()? {
+            Some(Token::Str(string)) => Ok(string),
+            other => Err(ReadErr(format!("Expected String, got {other:?}"))),
+        }
+    }
+
+    pub fn read_int(&mut self) -> Result<u64, ReadErr> {
+        match self.source.next_token()? {
+            Some(Token::Int(i)) => Ok(i),
+            other => Err(ReadErr(format!("Expected Integer, got {other:?}"))),
         }
     }
 
-    pub fn read_int(&mut self) -> Option<u64> {
+    /// Read an Integer if the next token is one; otherwise nothing is consumed
+    pub fn read_int_if_any(&mut self) -> Option<u64> {
+        let cur = self.source.cur;
         match self.source.next_token() {
-            Some(Token::Int(i)) => Some(i),
-            Some(other) => panic!("Expected Integer, got {other:?}"),
-            None => None,
+            Ok(Some(Token::Int(i))) => Some(i),
+            _ => {
+                self.source.cur = cur;
+                None
+            }
         }
     }
 
@@ -84,38 +107,7 @@ impl Val<ReaderContext> for ReaderContext {
     }
 
     fn prim_v(&self, ty: &str, b: &[u8]) -> RValue {
-        RValue::Prim(match ty {
-            "bool" => PValue::bool(b[0] != 0),
-            "char" => PValue::char(
-                match char::from_u32(u32::from_ne_bytes(b[..].try_into().unwrap())) {
-                    Some(c) => c,
-                    None => return RValue::Opaque,
-                },
-            ),
-            "u8" => PValue::u8(u8::from_ne_bytes(b[..].try_into().unwrap())),
-            "i8" => PValue::i8(i8::from_ne_bytes(b[..].try_into().unwrap())),
-            "u16" => PValue::u16(u16::from_ne_bytes(b[..].try_into().unwrap())),
-            "i16" => PValue::i16(i16::from_ne_bytes(b[..].try_into().unwrap())),
-            "u32" => PValue::u32(u32::from_ne_bytes(b[..].try_into().unwrap())),
-            "i32" => PValue::i32(i32::from_ne_bytes(b[..].try_into().unwrap())),
-            "u64" => PValue::u64(u64::from_ne_bytes(b[..].try_into().unwrap())),
-            "i64" => PValue::i64(i64::from_ne_bytes(b[..].try_into().unwrap())),
-            "usize" => PValue::usize(match b.len() {
-                4 => u32::from_ne_bytes(b[..].try_into().unwrap()) as u64,
-                8 => u64::from_ne_bytes(b[..].try_into().unwrap()),
-                _ => panic!("Not a usize: {b:?}"),
-            }),
-            "isize" => PValue::isize(match b.len() {
-                4 => i32::from_ne_bytes(b[..].try_into().unwrap()) as i64,
-                8 => i64::from_ne_bytes(b[..].try_into().unwrap()),
-                _ => panic!("Not a isize: {b:?}"),
-            }),
-            "u128" => PValue::u128(u128::from_ne_bytes(b[..].try_into().unwrap())),
-            "i128" => PValue::i128(i128::from_ne_bytes(b[..].try_into().unwrap())),
-            "f32" => PValue::f32(f32::from_ne_bytes(b[..].try_into().unwrap())),
-            "f64" => PValue::f64(f64::from_ne_bytes(b[..].try_into().unwrap())),
-            _ => panic!("unknown ty `{ty}`"),
-        })
+        self.prim(ty, b).unwrap()
     }
 
     fn bytes_v(&self, ty: &str, val: Bytes) -> RValue {
@@ -133,17 +125,7 @@ impl Val<ReaderContext> for ReaderContext {
     }
 
     fn ref_v(&self, ty: &str, addr: Addr) -> RValue {
-        if let Some(val) = self.env.get(&addr) {
-            RValue::Ref {
-                typename: ty.parse().unwrap(),
-                addr: RefAddr::Addr(addr),
-                value: Box::new(val.clone()),
-            }
-        } else {
-            RValue::UnresolvedRef {
-                addr: RefAddr::Addr(addr),
-            }
-        }
+        self.reference(ty.parse().unwrap(), addr)
     }
 
     fn struct_v<I: Iterator<Item = (String, RValue)>>(&self, _: &str, _: I) -> RValue {
@@ -164,6 +146,55 @@ impl Val<ReaderContext> for ReaderContext {
 }
 
 impl ReaderContext {
+    fn prim(&self, ty: &str, b: &[u8]) -> Result<RValue, ReadErr> {
+        fn bytes<const N: usize>(b: &[u8]) -> Result<[u8; N], ReadErr> {
+            b.try_into()
+                .map_err(|_| ReadErr(format!("Expected {N} bytes, got {b:?}")))
+        }
+        Ok(RValue::Prim(match ty {
+            "bool" => PValue::bool(bytes::<1>(b)?[0] != 0),
+            "char" => PValue::char(match char::from_u32(u32::from_ne_bytes(bytes(b)?)) {
+                Some(c) => c,
+                None => return Ok(RValue::Opaque),
+            }),
+            "u8" => PValue::u8(u8::from_ne_bytes(bytes(b)?)),
+            "i8" => PValue::i8(i8::from_ne_bytes(bytes(b)?)),
+            "u16" => PValue::u16(u16::from_ne_bytes(bytes(b)?)),
+            "i16" => PValue::i16(i16::from_ne_bytes(bytes(b)?)),
+            "u32" => PValue::u32(u32::from_ne_bytes(bytes(b)?)),
+            "i32" => PValue::i32(i32::from_ne_bytes(bytes(b)?)),
+            "u64" => PValue::u64(u64::from_ne_bytes(bytes(b)?)),
+            "i64" => PValue::i64(i64::from_ne_bytes(bytes(b)?)),
+            "usize" => PValue::usize(match b.len() {
+                4 => u32::from_ne_bytes(bytes(b)?) as u64,
+                _ => u64::from_ne_bytes(bytes(b)?),
+            }),
+            "isize" => PValue::isize(match b.len() {
+                4 => i32::from_ne_bytes(bytes(b)?) as i64,
+                _ => i64::from_ne_bytes(bytes(b)?),
+            }),
+            "u128" => PValue::u128(u128::from_ne_bytes(bytes(b)?)),
+            "i128" => PValue::i128(i128::from_ne_bytes(bytes(b)?)),
+            "f32" => PValue::f32(f32::from_ne_bytes(bytes(b)?)),
+            "f64" => PValue::f64(f64::from_ne_bytes(bytes(b)?)),
+            _ => return Err(ReadErr(format!("Unknown ty `{ty}`"))),
+        }))
+    }
+
+    fn reference(&self, typename: RefType, addr: Addr) -> RValue {
+        if let Some(val) = self.env.get(&addr) {
+            RValue::Ref {
+                typename,
+                addr: RefAddr::Addr(addr),
+                value: Box::new(val.clone()),
+            }
+        } else {
+            RValue::UnresolvedRef {
+                addr: RefAddr::Addr(addr),
+            }
+        }
+    }
+
     fn strlit_v(&self, v: Vec<u8>) -> Option<RValue> {
         Some(RValue::String {
             typename: StringType::StrLit,
@@ -256,23 +287,37 @@ impl SourceReader {
         self.cur = offset;
     }
 
-    fn next_token(&mut self) -> Option<Token> {
+    fn next_token(&mut self) -> Result<Option<Token>, ReadErr> {
         if self.cur >= self.source.len() {
-            return None;
+            return Ok(None);
         }
         let p = self.cur;
         let q = self.next_char(b' ');
+        if self.source.get(q) != b' ' {
+            // every token is followed by a space
+            return Err(ReadErr(format!("Unterminated token at {p}")));
+        }
         let tok = self.source.slice(p, q);
         if matches!(tok, &[b'"']) {
             self.cur += 2;
+            if self.cur >= self.source.len() {
+                return Err(ReadErr(format!("Unterminated string at {p}")));
+            }
             let r = self.next_char(b'"');
+            if self.source.get(r) != b'"' {
+                return Err(ReadErr(format!("Unterminated string at {p}")));
+            }
             let bytes = self.source.slice(self.cur, r).to_owned();
             self.cur = r + 1;
-            let tok = String::from_utf8(bytes).unwrap();
-            return Some(Token::Str(tok));
+            let tok = String::from_utf8(bytes)
+                .map_err(|e| ReadErr(format!("Invalid string at {p}: {e}")))?;
+            return Ok(Some(Token::Str(tok)));
         }
         if matches!(tok, &[b'#']) {
             self.cur += 2;
+            if self.cur + 4 > self.source.len() {
+                return Err(ReadErr(format!("Unterminated bytes at {p}")));
+            }
             let len = u32::from_ne_bytes([
                 self.source.get(self.cur),
                 self.source.get(self.cur + 1),
@@ -280,19 +325,23 @@ impl SourceReader {
                 self.source.get(self.cur + 3),
             ]);
             self.cur += 4;
+            if self.cur + len as usize > self.source.len() {
+                return Err(ReadErr(format!("Unterminated bytes at {p}")));
+            }
             let bytes = self
                 .source
                 .slice(self.cur, self.cur + len as usize)
                 .to_owned();
             self.cur += len as usize;
-            return Some(Token::Bytes(Bytes::from(bytes)));
+            return Ok(Some(Token::Bytes(Bytes::from(bytes))));
         }
         self.cur = q + 1; // assume one space as delimiter
-        let tok = std::str::from_utf8(tok).unwrap();
-        return Some(match tok.parse() {
+        let tok =
+            std::str::from_utf8(tok).map_err(|e| ReadErr(format!("Invalid token at {p}: {e}")))?;
+        Ok(Some(match tok.parse() {
             Ok(n) => Token::Int(n),
             Err(_) => Token::Op(tok.to_owned()),
-        });
+        }))
     }
 
     fn next_char(&self, c: u8) -> usize {
@@ -309,103 +358,99 @@ impl SourceReader {
     /// First, tokenize the binary stream.
     /// Then, push some primitives onto the value stack.
     /// When we read an `op` token, pop off some values from the stack based on the `op`.
-    pub fn read_values(&mut self, ctx: &mut ReaderContext) -> Vec<(String, RValue)> {
+    pub fn read_values(
+        &mut self,
+        ctx: &mut ReaderContext,
+    ) -> Result<Vec<(String, RValue)>, ReadErr> {
         let mut str_stack = Vec::<String>::new();
         let mut byte_stack = Vec::<Bytes>::new();
         let mut int_stack = Vec::<u64>::new();
         let mut val_stack = Vec::<RValue>::new();
         let mut names = Vec::<String>::new();
 
-        while let Some(tok) = self.next_token() {
+        while let Some(tok) = self.next_token()? {
             match tok {
                 Token::Str(s) => str_stack.push(s),
                 Token::Bytes(s) => byte_stack.push(s),
                 Token::Int(x) => int_stack.push(x),
                 Token::Op(op) => {
                     if op == "setenv" {
-                        let addr = Addr::new(byte_stack.pop().unwrap().as_bytes());
-                        let val = val_stack.pop().unwrap();
+                        let addr = addr(pop(&mut byte_stack, &op)?)?;
+                        let val = pop(&mut val_stack, &op)?;
                         ctx.set_env(addr, val);
                     } else if op == "prim" {
-                        let val = byte_stack.pop().unwrap();
-                        let ty = str_stack.pop().unwrap();
-                        val_stack.push(ctx.prim_v(&ty, val.as_bytes()));
+                        let val = pop(&mut byte_stack, &op)?;
+                        let ty = pop(&mut str_stack, &op)?;
+                        val_stack.push(ctx.prim(&ty, val.as_bytes())?);
                     } else if op == "bytes" {
-                        let val = byte_stack.pop().unwrap();
-                        let ty = str_stack.pop().unwrap();
+                        let val = pop(&mut byte_stack, &op)?;
+                        let ty = pop(&mut str_stack, &op)?;
                         val_stack.push(ctx.bytes_v(&ty, val));
                     } else if op == "arr" {
-                        let size = int_stack.pop().unwrap();
-                        let mut elem = Vec::new();
-                        for _ in 0..size {
-                            elem.push(val_stack.pop().unwrap());
-                        }
-                        val_stack.push(ctx.arr_v(elem.into_iter().rev()));
+                        let elem = pop_n(&mut val_stack, pop(&mut int_stack, &op)?, &op)?;
+                        val_stack.push(ctx.arr_v(elem.into_iter()));
                     } else if op == "ref" {
-                        let addr = Addr::new(byte_stack.pop().unwrap().as_bytes());
-                        let ty = str_stack.pop().unwrap();
-                        val_stack.push(ctx.ref_v(&ty, addr));
+                        let addr = addr(pop(&mut byte_stack, &op)?)?;
+                        let ty = pop(&mut str_stack, &op)?;
+                        let ty = ty
+                            .parse()
+                            .map_err(|_| ReadErr(format!("Unknown reference type `{ty}`")))?;
+                        val_stack.push(ctx.reference(ty, addr));
                     } else if op == "struct" {
-                        let name = str_stack.pop().unwrap();
-                        let fc = int_stack.pop().unwrap();
+                        let name = pop(&mut str_stack, &op)?;
+                        let fc = pop(&mut int_stack, &op)?;
                         let mut field = Vec::new();
                         for _ in 0..fc {
-                            let val = val_stack.pop().unwrap();
-                            let n = str_stack.pop().unwrap();
+                            let val = pop(&mut val_stack, &op)?;
+                            let n = pop(&mut str_stack, &op)?;
                             field.push((n, val));
                         }
                         val_stack.push(ctx.struct_v(name, field.into_iter().rev()));
                     } else if op == "enum" {
-                        let variant = str_stack.pop().unwrap();
-                        let name = str_stack.pop().unwrap();
+                        let variant = pop(&mut str_stack, &op)?;
+                        let name = pop(&mut str_stack, &op)?;
                         val_stack.push(ctx.enumerate_v(name, variant));
                     } else if op == "unit" {
                         val_stack.push(ctx.unit_v());
                     } else if op == "opaque" {
                         val_stack.push(ctx.opaque_v());
                     } else if op == "strlit" {
-                        let val = byte_stack.pop().unwrap();
+                        let val = pop(&mut byte_stack, &op)?;
                         val_stack.push(
                             ctx.strlit_v(val.into_bytes())
                                 .unwrap_or_else(|| ctx.opaque_v()),
                         );
                     } else if op == "union_decl" {
-                        let vc = int_stack.pop().unwrap();
-                        let mut variants = Vec::new();
-                        for _ in 0..vc {
-                            variants.push(str_stack.pop().unwrap());
-                        }
-                        variants.reverse();
-                        let name = str_stack.pop().unwrap();
+                        let vc = pop(&mut int_stack, &op)?;
+                        let variants = pop_n(&mut str_stack, vc, &op)?;
+                        let name = pop(&mut str_stack, &op)?;
                         let ty = UnionType {
                             name: name,
                             variants,
                         };
-                        let index = int_stack.pop().unwrap() as usize;
-                        let fc = int_stack.pop().unwrap();
+                        let index = pop(&mut int_stack, &op)? as usize;
+                        if index >= ty.variants.len() {
+                            return Err(ReadErr(format!(
+                                "Variant {index} out of bounds of `{}`",
+                                ty.name
+                            )));
+                        }
+                        let fc = pop(&mut int_stack, &op)?;
                         let mut field = Vec::new();
                         for _ in 0..fc {
-                            let val = val_stack.pop().unwrap();
-                            let n = str_stack.pop().unwrap();
+                            let val = pop(&mut val_stack, &op)?;
+                            let n = pop(&mut str_stack, &op)?;
                             field.push((n, val));
                         }
                         val_stack.push(ctx.union_v(&ty, index, field.into_iter().rev()));
                     } else if op == "vec" {
-                        let size = int_stack.pop().unwrap();
-                        let mut elem = Vec::new();
-                        for _ in 0..size {
-                            elem.push(val_stack.pop().unwrap());
-                        }
-                        val_stack.push(ctx.vector_v(elem.into_iter().rev()));
+                        let elem = pop_n(&mut val_stack, pop(&mut int_stack, &op)?, &op)?;
+                        val_stack.push(ctx.vector_v(elem.into_iter()));
                     } else if op == "slice" {
-                        let size = int_stack.pop().unwrap();
-                        let mut elem = Vec::new();
-                        for _ in 0..size {
-                            elem.push(val_stack.pop().unwrap());
-                        }
-                        val_stack.push(ctx.slice_v(elem.into_iter().rev()));
+                        let elem = pop_n(&mut val_stack, pop(&mut int_stack, &op)?, &op)?;
+                        val_stack.push(ctx.slice_v(elem.into_iter()));
                     } else if op == "name" {
-                        names.push(str_stack.pop().unwrap());
+                        names.push(pop(&mut str_stack, &op)?);
                     }
                 }
             }
@@ -414,8 +459,35 @@ impl SourceReader {
         val_stack.iter_mut().for_each(|v| {
             v.lift();
         });
-        assert_eq!(names.len(), val_stack.len());
-        names.into_iter().zip(val_stack.into_iter()).collect()
+        if names.len() != val_stack.len() {
+            return Err(ReadErr(format!(
+                "{} names for {} values",
+                names.len(),
+                val_stack.len()
+            )));
+        }
+        Ok(names.into_iter().zip(val_stack.into_iter()).collect())
+    }
+}
+
+fn pop<T>(stack: &mut Vec<T>, op: &str) -> Result<T, ReadErr> {
+    stack
+        .pop()
+        .ok_or_else(|| ReadErr(format!("Stack underflow at `{op}`")))
+}
+
+/// Pop the last `n` items, in order
+fn pop_n<T>(stack: &mut Vec<T>, n: u64, op: &str) -> Result<Vec<T>, ReadErr> {
+    if n > stack.len() as u64 {
+        return Err(ReadErr(format!("Stack underflow at `{op}`")));
+    }
+    Ok(stack.split_off(stack.len() - n as usize))
+}
+
+fn addr(bytes: Bytes) -> Result<Addr, ReadErr> {
+    match bytes.len() {
+        4 | 8 => Ok(Addr::new(bytes.as_bytes())),
+        _ => Err(ReadErr(format!("Not an address: {:?}", bytes.as_bytes()))),
     }
 }
 
@@ -0,0 +1,23 @@
+use std::{thread, time::Duration};
+
+fn work(n: u32) -> u32 {
+    if n == 0 {
+        return 0;
+    }
+    thread::sleep(Duration::from_millis(100));
+    work(n - 1) + 1
+}
+
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn prctl(option: i32, ...) -> i32;
+}
+
+fn main() {
+    // `PR_SET_PTRACER`, `PR_SET_PTRACER_ANY`: allow the debugger to attach, even though it is not our parent
+    #[cfg(target_os = "linux")]
+    unsafe {
+        prctl(0x59616d61, -1i64);
+    }
+    work(30);
+}
@@ -0,0 +1,22 @@
+use std::panic;
+
+fn inner(n: i32) -> i32 {
+    if n > 1 {
+        panic!("too big");
+    }
+    n
+}
+
+fn outer(n: i32) -> i32 {
+    inner(n) + 1
+}
+
+fn recover(n: i32) -> Option<i32> {
+    panic::catch_unwind(|| outer(n)).ok()
+}
+
+fn main() {
+    recover(1);
+    recover(2);
+    recover(1);
+}
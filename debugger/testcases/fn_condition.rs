@@ -0,0 +1,22 @@
+fn noop() {}
+
+fn hello(n: i32) -> i32 {
+    n + 1
+}
+
+fn check(i: i32) -> Result<i32, ()> {
+    if i % 2 == 0 {
+        Ok(i)
+    } else {
+        Err(())
+    }
+}
+
+fn main() {
+    for i in 0..3 {
+        noop();
+        hello(i);
+    }
+    check(2).ok();
+    check(3).ok();
+}
@@ -0,0 +1,22 @@
+fn leaf(i: i32) -> i32 {
+    i * 2
+}
+
+fn parse(i: i32) -> Result<i32, ()> {
+    let j = leaf(i);
+    if i == 3 {
+        panic!("oops");
+    }
+    if j % 4 == 0 {
+        Ok(j)
+    } else {
+        Err(())
+    }
+}
+
+fn main() {
+    for i in 0..3 {
+        parse(i).ok();
+    }
+    std::panic::catch_unwind(|| parse(3)).ok();
+}
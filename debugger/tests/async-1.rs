@@ -28,6 +28,7 @@ async fn main() -> Result<()> {
         match &event {
             Event::Breakpoint { .. } => (),
             Event::FunctionCall { function_name, .. } => {}
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn { function_name, .. } => {}
         }
     }
@@ -55,6 +55,7 @@ async fn main() -> Result<()> {
                     );
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
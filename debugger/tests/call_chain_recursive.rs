@@ -47,6 +47,7 @@ async fn main() -> Result<()> {
                     }
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
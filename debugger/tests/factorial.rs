@@ -43,6 +43,7 @@ async fn main() -> Result<()> {
                     assert_eq!(arguments.len(), 1);
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
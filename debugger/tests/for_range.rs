@@ -38,6 +38,7 @@ async fn main() -> Result<()> {
                     }
                 );
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn { function_name, .. } => {
                 assert_eq!(
                     function_name,
@@ -151,6 +151,7 @@ async fn main() -> Result<()> {
                     other => panic!("Unexpected {other:?}"),
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn { function_name, .. } => {
                 println!("#{i} FunctionReturn `{function_name}`");
                 match i {
@@ -0,0 +1,56 @@
+mod util;
+use util::*;
+
+use anyhow::Result;
+use firedbg_rust_debugger::{Bytes, Debugger, Event, EventStream};
+use pretty_assertions::assert_eq;
+use sea_streamer::{Buffer, Consumer, Message, Producer};
+use std::{process::Command, time::Duration};
+
+#[tokio::test]
+async fn main() -> Result<()> {
+    let testcase = "fn_attach";
+    let (producer, consumer) = setup(testcase).await?;
+
+    let mut debugger_params = debugger_params_from_file(testcase);
+    let mut child = Command::new(&debugger_params.binary).spawn()?;
+    // Attach partway through the recursion; the outer calls of `work` are already running
+    std::thread::sleep(Duration::from_millis(1000));
+    debugger_params.pid = Some(child.id() as u64);
+
+    Debugger::run(debugger_params, producer.clone());
+    assert!(child.wait()?.success());
+
+    producer.end().await?;
+
+    let mut events = Vec::new();
+    while let Ok(message) = consumer.next().await {
+        let event = EventStream::read_from(Bytes::from(message.message().into_bytes()));
+        println!("#{} {event:?}", events.len());
+        events.push(event);
+    }
+
+    // Only the calls made after attaching are recorded; neither `main` nor the outer calls return
+    let n = events.len() / 2;
+    assert!(n > 0 && n < 30, "{n} calls recorded");
+    let summary: Vec<_> = events
+        .iter()
+        .map(|event| match event {
+            Event::FunctionCall { function_name, .. } => format!("F {function_name}"),
+            Event::FunctionReturn {
+                function_name,
+                return_value,
+                ..
+            } => format!("R {function_name} {return_value}"),
+            Event::FunctionUnwind { function_name, .. } => format!("U {function_name}"),
+            Event::Breakpoint { reason, .. } => format!("B {reason:?}"),
+        })
+        .collect();
+    let expected: Vec<_> = std::iter::repeat("F fn_attach::work".to_owned())
+        .take(n)
+        .chain((0..n).map(|i| format!("R fn_attach::work {i}u32")))
+        .collect();
+    assert_eq!(summary, expected);
+
+    Ok(())
+}
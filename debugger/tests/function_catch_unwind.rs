@@ -0,0 +1,113 @@
+mod util;
+use util::*;
+
+use anyhow::Result;
+use firedbg_rust_debugger::{
+    Bytes, Debugger, Event, EventStream, RValue, Reason, RUST_PANIC_BP_ID, RUST_PANIC_CLEANUP_BP_ID,
+};
+use pretty_assertions::assert_eq;
+use sea_streamer::{Buffer, Consumer, Message, Producer};
+
+#[tokio::test]
+async fn main() -> Result<()> {
+    let testcase = "fn_catch_unwind";
+    let (producer, consumer) = setup(testcase).await?;
+
+    let debugger_params = debugger_params_from_file(testcase);
+    Debugger::run(debugger_params, producer.clone());
+
+    producer.end().await?;
+
+    let mut events = Vec::new();
+    for i in 0..22 {
+        let payload = consumer.next().await?.message().into_bytes();
+        let event = EventStream::read_from(Bytes::from(payload));
+        println!("#{i} {event:?}");
+        events.push(event);
+    }
+
+    let summary: Vec<_> = events
+        .iter()
+        .map(|event| match event {
+            Event::FunctionCall { function_name, .. } => format!("F {function_name}"),
+            Event::FunctionReturn {
+                function_name,
+                return_value,
+                ..
+            } => format!("R {function_name} {return_value}"),
+            Event::FunctionUnwind { function_name, .. } => format!("U {function_name}"),
+            Event::Breakpoint { reason, .. } => format!("B {reason:?}"),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            "F fn_catch_unwind::main",
+            "F fn_catch_unwind::recover",
+            "F fn_catch_unwind::outer",
+            "F fn_catch_unwind::inner",
+            "R fn_catch_unwind::inner 1i32",
+            "R fn_catch_unwind::outer 2i32",
+            "R fn_catch_unwind::recover core::option::Option::<i32>::Some(2i32)",
+            "F fn_catch_unwind::recover",
+            "F fn_catch_unwind::outer",
+            "F fn_catch_unwind::inner",
+            "B Panic",
+            "U fn_catch_unwind::inner",
+            "U fn_catch_unwind::outer",
+            "B Catch",
+            "R fn_catch_unwind::recover core::option::Option::<i32>::None",
+            "F fn_catch_unwind::recover",
+            "F fn_catch_unwind::outer",
+            "F fn_catch_unwind::inner",
+            "R fn_catch_unwind::inner 1i32",
+            "R fn_catch_unwind::outer 2i32",
+            "R fn_catch_unwind::recover core::option::Option::<i32>::Some(2i32)",
+            "R fn_catch_unwind::main ()",
+        ]
+    );
+
+    let frame_id = |i: usize| match &events[i] {
+        Event::FunctionCall { frame_id, .. }
+        | Event::FunctionReturn { frame_id, .. }
+        | Event::FunctionUnwind { frame_id, .. }
+        | Event::Breakpoint { frame_id, .. } => *frame_id,
+    };
+    // The panic is raised in `inner`, which is unwound before `outer`
+    assert_eq!(frame_id(10), frame_id(9));
+    assert_eq!(frame_id(11), frame_id(9));
+    assert_eq!(frame_id(12), frame_id(8));
+    // Caught in `recover`, which returns normally
+    assert_eq!(frame_id(13), frame_id(7));
+    assert_eq!(frame_id(14), frame_id(7));
+
+    let Event::Breakpoint { breakpoint_id, .. } = &events[10] else {
+        unreachable!()
+    };
+    assert_eq!(*breakpoint_id, RUST_PANIC_BP_ID.0);
+    let Event::Breakpoint {
+        breakpoint_id,
+        reason,
+        locals,
+        ..
+    } = &events[13]
+    else {
+        unreachable!()
+    };
+    assert_eq!(*breakpoint_id, RUST_PANIC_CLEANUP_BP_ID.0);
+    assert_eq!(reason, &Reason::Catch);
+    let (name, value) = &locals[0];
+    assert_eq!(name, "fn");
+    let RValue::String { value, .. } = value else {
+        panic!("{value:?}");
+    };
+    assert_eq!(value, "fn_catch_unwind::recover");
+    let (name, value) = &locals[1];
+    assert_eq!(name, "location");
+    let RValue::String { value, .. } = value else {
+        panic!("{value:?}");
+    };
+    assert!(value.contains("fn_catch_unwind.rs:15:"), "{value}");
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+mod util;
+use util::*;
+
+use anyhow::Result;
+use firedbg_rust_debugger::{BreakpointType, Bytes, Debugger, EventStream};
+use sea_streamer::{Buffer, Consumer, Message, Producer};
+
+#[tokio::test]
+async fn main() -> Result<()> {
+    let testcase = "fn_condition";
+    let (producer, consumer) = setup(testcase).await?;
+
+    let mut debugger_params = debugger_params_from_file(testcase);
+    for bp in debugger_params.breakpoints.iter() {
+        let BreakpointType::FunctionCall { fn_name } = &bp.breakpoint_type else {
+            continue;
+        };
+        let condition = match fn_name.as_str() {
+            // Never recorded; its frame must still be popped
+            "noop" => "false",
+            "hello" => "n == 1",
+            // Evaluated on return
+            "check" => "return_value.is_err()",
+            _ => continue,
+        };
+        debugger_params
+            .conditions
+            .insert(bp.id, condition.parse().unwrap());
+    }
+    assert_eq!(debugger_params.conditions.len(), 3);
+
+    Debugger::run(debugger_params, producer.clone());
+
+    producer.end().await?;
+
+    let expected = vec![
+        Expected::FnCall {
+            name: "main".into(),
+            args: vec![],
+        },
+        Expected::FnCall {
+            name: "hello".into(),
+            args: vec!["1i32".into()],
+        },
+        Expected::FnRet {
+            name: "hello".into(),
+            value: "2i32".into(),
+        },
+        Expected::FnCall {
+            name: "check".into(),
+            args: vec!["3i32".into()],
+        },
+        Expected::FnRet {
+            name: "check".into(),
+            value: "core::result::Result::<i32, ()>::Err(())".into(),
+        },
+        Expected::FnRet {
+            name: "main".into(),
+            value: "()".into(),
+        },
+    ];
+
+    let mut events = Vec::new();
+    for i in 0..expected.len() {
+        let payload = consumer.next().await?.message().into_bytes();
+        let event = EventStream::read_from(Bytes::from(payload));
+        println!("#{i} {:?}", event);
+        events.push(event);
+    }
+
+    verify(testcase, events, expected);
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+mod util;
+use util::*;
+
+use anyhow::Result;
+use firedbg_rust_debugger::{BreakpointType, Bytes, Debugger, Event, EventStream};
+use pretty_assertions::assert_eq;
+use sea_streamer::{Buffer, Consumer, Message, Producer};
+
+#[tokio::test]
+async fn main() -> Result<()> {
+    let testcase = "fn_condition_tree";
+    let (producer, consumer) = setup(testcase).await?;
+
+    let mut debugger_params = debugger_params_from_file(testcase);
+    for bp in debugger_params.breakpoints.iter() {
+        if matches!(&bp.breakpoint_type, BreakpointType::FunctionCall { fn_name } if fn_name == "parse")
+        {
+            debugger_params
+                .conditions
+                .insert(bp.id, "return_value.is_err()".parse().unwrap());
+        }
+    }
+    assert_eq!(debugger_params.conditions.len(), 1);
+
+    Debugger::run(debugger_params, producer.clone());
+
+    producer.end().await?;
+
+    let mut events = Vec::new();
+    for i in 0..12 {
+        let payload = consumer.next().await?.message().into_bytes();
+        let event = EventStream::read_from(Bytes::from(payload));
+        println!("#{i} {event:?}");
+        events.push(event);
+    }
+
+    let summary: Vec<_> = events
+        .iter()
+        .map(|event| match event {
+            Event::FunctionCall {
+                function_name,
+                arguments,
+                ..
+            } => {
+                let arguments: Vec<_> = arguments.iter().map(|(_, v)| v.to_string()).collect();
+                format!("F {function_name}({})", arguments.join(", "))
+            }
+            Event::FunctionReturn {
+                function_name,
+                return_value,
+                ..
+            } => format!("R {function_name} {return_value}"),
+            Event::FunctionUnwind { function_name, .. } => format!("U {function_name}"),
+            Event::Breakpoint { reason, .. } => format!("B {reason:?}"),
+        })
+        .collect();
+    // The calls of `leaf` inside a `parse` which returns `Ok` are not recorded either
+    assert_eq!(
+        summary,
+        [
+            "F fn_condition_tree::main()",
+            "F fn_condition_tree::parse(1i32)",
+            "F fn_condition_tree::leaf(1i32)",
+            "R fn_condition_tree::leaf 2i32",
+            "R fn_condition_tree::parse core::result::Result::<i32, ()>::Err(())",
+            // A call which panics is recorded, with everything inside
+            "F fn_condition_tree::parse(3i32)",
+            "F fn_condition_tree::leaf(3i32)",
+            "R fn_condition_tree::leaf 6i32",
+            "B Panic",
+            "U fn_condition_tree::parse",
+            "B Catch",
+            "R fn_condition_tree::main ()",
+        ]
+    );
+
+    let (frame_id, clock) = (
+        |i: usize| match &events[i] {
+            Event::FunctionCall { frame_id, .. }
+            | Event::FunctionReturn { frame_id, .. }
+            | Event::FunctionUnwind { frame_id, .. }
+            | Event::Breakpoint { frame_id, .. } => *frame_id,
+        },
+        |i: usize| match &events[i] {
+            Event::FunctionCall { clock, .. }
+            | Event::FunctionReturn { clock, .. }
+            | Event::FunctionUnwind { clock, .. } => *clock,
+            Event::Breakpoint { .. } => unreachable!(),
+        },
+    );
+    for (parse, leaf, leaf_ret, parse_ret) in [(1, 2, 3, 4), (5, 6, 7, 9)] {
+        // `leaf` is called by `parse`, and returns in between its call and return
+        assert_eq!(frame_id(leaf), frame_id(parse) + 1);
+        assert_eq!(frame_id(leaf_ret), frame_id(leaf));
+        assert_eq!(frame_id(parse_ret), frame_id(parse));
+        assert!(clock(parse) <= clock(leaf));
+        assert!(clock(leaf) <= clock(leaf_ret));
+        assert!(clock(leaf_ret) <= clock(parse_ret));
+    }
+    // The panic is raised in `parse`
+    assert_eq!(frame_id(8), frame_id(5));
+
+    Ok(())
+}
@@ -177,6 +177,7 @@ async fn main() -> Result<()> {
                     }
                 );
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn { function_name, .. } => {
                 println!("FunctionReturn {function_name}");
                 assert_eq!(
@@ -4,7 +4,7 @@ use util::*;
 use anyhow::Result;
 use firedbg_rust_debugger::{
     Breakpoint, BreakpointType, Bytes, Debugger, Event, EventStream, LineColumn, RValue, Reason,
-    VariableCapture, RUST_PANIC_BP_ID,
+    VariableCapture, RUST_PANIC_BP_ID, RUST_PANIC_CLEANUP_BP_ID,
 };
 use pretty_assertions::assert_eq;
 use sea_streamer::{Buffer, Consumer, Message, Producer};
@@ -120,7 +120,10 @@ async fn main() -> Result<()> {
 
     let the_world = r#"{"type":"Struct","typename":"fn_call_panic::World","fields":{"nth":{"type":"Prim","typename":"i32","value":99}}}"#;
 
-    for i in 0..11 {
+    // The frame ids of `main` and `hello_3`
+    let mut frame_ids = Vec::new();
+
+    for i in 0..14 {
         let payload = consumer.next().await?.message().into_bytes();
         let event = EventStream::read_from(Bytes::from(payload));
         println!("#{i} {event:?}");
@@ -128,9 +131,11 @@ async fn main() -> Result<()> {
             Event::FunctionCall {
                 function_name,
                 arguments: values,
+                frame_id,
                 ..
             } => match i {
                 0 => {
+                    frame_ids.push(frame_id);
                     assert_eq!(values.len(), 0);
                     assert_eq!(function_name, "fn_call_panic::main");
                 }
@@ -170,6 +175,7 @@ async fn main() -> Result<()> {
                     }
                 }
                 9 => {
+                    frame_ids.push(frame_id);
                     assert_eq!(values.len(), 1);
                     assert_eq!(function_name, "fn_call_panic::hello_3");
                     let (name, value) = &values[0];
@@ -183,6 +189,22 @@ async fn main() -> Result<()> {
                 }
                 other => panic!("Unexpected {other:?}"),
             },
+            Event::FunctionUnwind {
+                function_name,
+                frame_id,
+                ..
+            } => match i {
+                // Unwound through `hello_3` and `main` by the panic, caught by the standard library
+                11 => {
+                    assert_eq!(function_name, "fn_call_panic::hello_3");
+                    assert_eq!(frame_id, frame_ids[1]);
+                }
+                12 => {
+                    assert_eq!(function_name, "fn_call_panic::main");
+                    assert_eq!(frame_id, frame_ids[0]);
+                }
+                other => panic!("Unexpected {other:?}"),
+            },
             Event::FunctionReturn { function_name, .. } => match i {
                 3 | 7 => {
                     assert_eq!(
@@ -192,9 +214,27 @@ async fn main() -> Result<()> {
                 }
                 4 => assert_eq!(function_name, "fn_call_panic::hello_1"),
                 8 => assert_eq!(function_name, "fn_call_panic::hello_2"),
-                9 => assert_eq!(function_name, "fn_call_panic::main"),
                 other => panic!("Unexpected {other:?}"),
             },
+            Event::Breakpoint {
+                breakpoint_id,
+                reason,
+                locals: values,
+                frame_id,
+                ..
+            } if i == 13 => {
+                assert_eq!(breakpoint_id, RUST_PANIC_CLEANUP_BP_ID.0);
+                assert_eq!(reason, Reason::Catch);
+                // No traced frame is left
+                assert_eq!(frame_id, 0);
+                let (name, value) = &values[0];
+                assert_eq!(name, "fn");
+                if let RValue::String { value, .. } = value {
+                    assert!(value.starts_with("std::rt::lang_start"), "{value}");
+                } else {
+                    panic!("{value:?}");
+                }
+            }
             Event::Breakpoint {
                 breakpoint_id,
                 reason,
@@ -54,6 +54,7 @@ async fn main() -> Result<()> {
                 }
                 println!("[{i}] {function_name}({})", event.format_arguments());
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
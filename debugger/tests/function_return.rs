@@ -57,6 +57,14 @@ async fn main() -> Result<()> {
             },
         ],
         arguments: vec![],
+        conditions: Default::default(),
+        dbg_conditions: Default::default(),
+        pid: None,
+        recording: Default::default(),
+        stdin: Default::default(),
+        env: Default::default(),
+        env_remove: Default::default(),
+        cwd: None,
     };
 
     let (producer, consumer) = setup(testcase).await?;
@@ -44,6 +44,14 @@ async fn main() -> Result<()> {
                 },
             ],
             arguments: vec![],
+            conditions: Default::default(),
+            dbg_conditions: Default::default(),
+            pid: None,
+            recording: Default::default(),
+            stdin: Default::default(),
+            env: Default::default(),
+            env_remove: Default::default(),
+            cwd: None,
         },
         producer.clone(),
     );
@@ -91,11 +99,18 @@ async fn main() -> Result<()> {
 
     let mut addr = 0;
 
-    for i in 0..2 {
+    let mut i = 0;
+    while i < 2 {
         let message = allocations.next().await?;
         let message = message.message();
         let message = message.as_str()?;
         let alloc: Allocation = serde_json::from_str(message)?;
+        if alloc.type_name.is_empty()
+            || !matches!(alloc.action, AllocAction::Alloc | AllocAction::Drop)
+        {
+            // Untyped allocations, reallocations and deallocations from the allocator
+            continue;
+        }
         assert_eq!(alloc.type_name, "object::Car");
         if i == 0 {
             assert_eq!(alloc.action, AllocAction::Alloc);
@@ -105,6 +120,7 @@ async fn main() -> Result<()> {
             assert_eq!(alloc.address, addr);
         }
         println!("{alloc:?}");
+        i += 1;
     }
 
     Ok(())
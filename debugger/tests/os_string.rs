@@ -32,6 +32,7 @@ async fn main() -> Result<()> {
                     );
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn { .. } => {}
         }
     }
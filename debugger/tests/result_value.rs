@@ -58,6 +58,7 @@ async fn main() -> Result<()> {
                 }
                 println!("[{i}] {function_name}({value})");
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
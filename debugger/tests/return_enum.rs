@@ -59,6 +59,7 @@ async fn main() -> Result<()> {
                     println!("[{i}] {function_name}({name} = {value}) ..");
                 }
             }
+            (_, Event::FunctionUnwind { .. }) => unreachable!(),
             (
                 _,
                 Event::FunctionReturn {
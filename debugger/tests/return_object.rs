@@ -106,6 +106,14 @@ async fn main() -> Result<()> {
             },
         ],
         arguments: vec![],
+        conditions: Default::default(),
+        dbg_conditions: Default::default(),
+        pid: None,
+        recording: Default::default(),
+        stdin: Default::default(),
+        env: Default::default(),
+        env_remove: Default::default(),
+        cwd: None,
     };
 
     let (producer, consumer) = setup(testcase).await?;
@@ -155,6 +163,7 @@ async fn main() -> Result<()> {
                     }
                 );
             }
+            (_, Event::FunctionUnwind { .. }) => unreachable!(),
             (
                 _,
                 Event::FunctionReturn {
@@ -24,6 +24,7 @@ async fn main() -> Result<()> {
         // println!("#{i} {:?}", event);
 
         match event {
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
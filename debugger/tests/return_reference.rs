@@ -25,6 +25,7 @@ async fn main() -> Result<()> {
         println!("#{i} {:?}", event);
         if matches!(i, 2 | 4 | 6 | 8 | 10 | 12 | 14 | 16 | 18 | 19) {
             match event {
+                Event::FunctionUnwind { .. } => unreachable!(),
                 Event::FunctionReturn { return_value, .. } => {
                     if let RValue::Ref { value, .. } = return_value {
                         match value.deref() {
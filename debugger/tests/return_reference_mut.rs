@@ -48,6 +48,7 @@ async fn main() -> Result<()> {
                     );
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 mut return_value,
@@ -23,6 +23,7 @@ async fn main() -> Result<()> {
         let event = EventStream::read_from(Bytes::from(payload));
         // println!("#{i} {:?}", event);
         match event {
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 mut return_value,
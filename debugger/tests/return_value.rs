@@ -471,12 +471,14 @@ async fn main() -> Result<()> {
                     .entry((thread_id, frame_id))
                     .or_insert((function_name, arg));
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 breakpoint_id: _,
                 thread_id,
                 frame_id,
                 function_name,
                 return_value,
+                ..
             } => {
                 let (fn_call_function_name, fn_call_arg) = hashmap
                     .remove(&(thread_id, frame_id))
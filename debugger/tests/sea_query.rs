@@ -43,6 +43,7 @@ async fn main() -> Result<()> {
                 }
                 println!("[{i}] {function_name}() -> {arguments:?}");
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 mut return_value,
@@ -62,6 +62,7 @@ async fn main() -> Result<()> {
                     }
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 thread_id,
                 function_name,
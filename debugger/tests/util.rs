@@ -50,6 +50,14 @@ pub fn debugger_params_from_file(testcase: &str) -> DebuggerParams {
         files,
         breakpoints,
         arguments: vec![],
+        conditions: Default::default(),
+        dbg_conditions: Default::default(),
+        pid: None,
+        recording: Default::default(),
+        stdin: Default::default(),
+        env: Default::default(),
+        env_remove: Default::default(),
+        cwd: None,
     }
 }
 
@@ -78,6 +86,14 @@ pub fn debugger_params_testbench(testcase: &str) -> DebuggerParams {
         files,
         breakpoints,
         arguments: vec![],
+        conditions: Default::default(),
+        dbg_conditions: Default::default(),
+        pid: None,
+        recording: Default::default(),
+        stdin: Default::default(),
+        env: Default::default(),
+        env_remove: Default::default(),
+        cwd: None,
     }
 }
 
@@ -121,6 +137,9 @@ pub async fn setup_1(
             package_name: testcase.to_owned(),
             target: testcase.to_owned(),
             arguments: vec![],
+            env: Default::default(),
+            env_remove: vec![],
+            cwd: None,
         }))
         .unwrap()
         .as_str(),
@@ -161,6 +180,7 @@ pub fn verify(testcase: &str, events: Vec<Event>, expected: Vec<Expected>) {
                 }
                 e => panic!("Expected {e:?}"),
             },
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn {
                 function_name,
                 return_value,
@@ -31,6 +31,7 @@ async fn main() -> Result<()> {
                     assert_eq!(arguments[0].1, RValue::Prim(PValue::i32(i / 2)));
                 }
             }
+            Event::FunctionUnwind { .. } => unreachable!(),
             Event::FunctionReturn { return_value, .. } => {
                 if i == 21 {
                     assert_eq!(return_value, RValue::Unit);
@@ -0,0 +1,237 @@
+//! Rebuild the logical call trees of `async fn`.
+//!
+//! A future can be polled by a different executor thread each time, so the per-thread stack
+//! does not tell which future awaited which. Futures are instead identified by the address of
+//! their generator state, and the parent of a future is the one whose poll was active on the
+//! same thread when it was first polled.
+//!
+//! The address of a future which is dropped before completion can be reused by another future.
+//! A new future is started when the function polled at the address changes, or the poll count
+//! restarts; a future whose poll has unwound is dropped.
+use std::collections::HashMap;
+
+use firedbg_rust_debugger::{PValue, RValue};
+use sea_orm::{NotSet, Set};
+
+use crate::entity::{future, future_poll};
+
+#[derive(Debug, Default)]
+pub struct AsyncTree {
+    /// Address -> futures which have not completed
+    pending: HashMap<u64, future::Model>,
+    /// (thread id, frame id) of the last poll -> completed futures waiting for its return value
+    completed: HashMap<(u64, u64), future::Model>,
+    /// Thread id -> (frame id, future id) of the active polls, innermost last
+    polls: HashMap<u64, Vec<(u64, i64)>>,
+    count: i64,
+}
+
+impl AsyncTree {
+    /// A future is polled in the frame `frame_id`. Returns the future to be saved and the poll to be inserted.
+    pub fn poll(
+        &mut self,
+        thread_id: u64,
+        frame_id: u64,
+        locals: &[(String, RValue)],
+    ) -> Option<(future::Model, future_poll::ActiveModel)> {
+        let (address, poll) = (local_u64(locals, "future")?, local_u64(locals, "poll")?);
+        let function_name = local_str(locals, "fn").unwrap_or_default();
+        if matches!(
+            self.pending.get(&address),
+            Some(future) if future.function_name != function_name || poll as i64 <= future.polls
+        ) {
+            // The previous future at this address has been dropped
+            self.pending.remove(&address);
+        }
+        let polls = self.polls.entry(thread_id).or_default();
+        let count = &mut self.count;
+        let future = self.pending.entry(address).or_insert_with(|| {
+            *count += 1;
+            future::Model {
+                // Set on save
+                run_id: 0,
+                id: *count,
+                address: address as i64,
+                function_name,
+                parent_id: polls.last().map(|(_, future_id)| *future_id),
+                thread_id: thread_id as i64,
+                frame_id: frame_id as i64,
+                polls: 0,
+                ready: false,
+                result: None,
+                pretty: None,
+            }
+        });
+        future.polls = poll as i64;
+        polls.push((frame_id, future.id));
+        let future_poll = future_poll::ActiveModel {
+            id: NotSet,
+            run_id: NotSet,
+            future_id: Set(future.id),
+            poll: Set(poll as i64),
+            thread_id: Set(thread_id as i64),
+            frame_id: Set(frame_id as i64),
+        };
+        Some((future.clone(), future_poll))
+    }
+
+    /// A future has completed in the frame `frame_id`. Returns the future to be saved.
+    pub fn ready(
+        &mut self,
+        thread_id: u64,
+        frame_id: u64,
+        locals: &[(String, RValue)],
+    ) -> Option<future::Model> {
+        let address = local_u64(locals, "future")?;
+        let mut future = self.pending.remove(&address)?;
+        future.ready = true;
+        if let Some(poll) = local_u64(locals, "poll") {
+            future.polls = poll as i64;
+        }
+        self.completed.insert((thread_id, frame_id), future.clone());
+        Some(future)
+    }
+
+    /// The frame `frame_id` has returned with `result`, or ended without returning if `None`. Returns the completed future
+    /// to be saved with the result, if it was its last poll.
+    pub fn frame_end(
+        &mut self,
+        thread_id: u64,
+        frame_id: u64,
+        result: Option<(&str, &str)>,
+    ) -> Option<future::Model> {
+        if let Some(polls) = self.polls.get_mut(&thread_id) {
+            if let Some(&(_, future_id)) = polls.last().filter(|(id, _)| *id == frame_id) {
+                polls.pop();
+                if result.is_none() {
+                    // The future is dropped by the panic
+                    self.pending.retain(|_, future| future.id != future_id);
+                }
+            }
+        }
+        let mut future = self.completed.remove(&(thread_id, frame_id))?;
+        if let Some((result, pretty)) = result {
+            future.result = Some(result.to_owned());
+            future.pretty = Some(pretty.to_owned());
+        }
+        Some(future)
+    }
+}
+
+fn local_u64(locals: &[(String, RValue)], name: &str) -> Option<u64> {
+    locals.iter().find_map(|(n, v)| match v {
+        RValue::Prim(PValue::u64(v)) if n == name => Some(*v),
+        _ => None,
+    })
+}
+
+fn local_str(locals: &[(String, RValue)], name: &str) -> Option<String> {
+    locals.iter().find_map(|(n, v)| match v {
+        RValue::String { value, .. } if n == name => Some(value.clone()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use firedbg_rust_debugger::StringType;
+    use pretty_assertions::assert_eq;
+
+    fn locals(function_name: &str, address: u64, poll: u64) -> Vec<(String, RValue)> {
+        vec![
+            (
+                "fn".into(),
+                RValue::String {
+                    typename: StringType::String,
+                    value: function_name.into(),
+                },
+            ),
+            ("future".into(), RValue::Prim(PValue::u64(address))),
+            ("poll".into(), RValue::Prim(PValue::u64(poll))),
+        ]
+    }
+
+    /// (id, function name, parent id, polls, ready, result)
+    fn summary(future: &future::Model) -> (i64, &str, Option<i64>, i64, bool, Option<&str>) {
+        (
+            future.id,
+            future.function_name.as_str(),
+            future.parent_id,
+            future.polls,
+            future.ready,
+            future.result.as_deref(),
+        )
+    }
+
+    #[test]
+    fn test_async_tree() {
+        let mut tree = AsyncTree::default();
+        // `main` awaits `fetch` twice, polled on different threads
+        let (future, poll) = tree.poll(1, 1, &locals("main", 100, 1)).unwrap();
+        assert_eq!(summary(&future), (1, "main", None, 1, false, None));
+        assert_eq!(poll.future_id.as_ref(), &1);
+        let (future, _) = tree.poll(1, 2, &locals("fetch", 200, 1)).unwrap();
+        assert_eq!(summary(&future), (2, "fetch", Some(1), 1, false, None));
+        assert_eq!(tree.frame_end(1, 2, Some(("Pending", "Pending"))), None);
+        assert_eq!(tree.frame_end(1, 1, Some(("Pending", "Pending"))), None);
+
+        let (future, poll) = tree.poll(2, 3, &locals("main", 100, 2)).unwrap();
+        assert_eq!(summary(&future), (1, "main", None, 2, false, None));
+        assert_eq!(poll.poll.as_ref(), &2);
+        let (future, _) = tree.poll(2, 4, &locals("fetch", 200, 2)).unwrap();
+        assert_eq!(summary(&future), (2, "fetch", Some(1), 2, false, None));
+        let future = tree.ready(2, 4, &locals("fetch", 200, 2)).unwrap();
+        assert_eq!(summary(&future), (2, "fetch", Some(1), 2, true, None));
+        let future = tree
+            .frame_end(2, 4, Some(("Ready(1)", "Ready(1)")))
+            .unwrap();
+        assert_eq!(
+            summary(&future),
+            (2, "fetch", Some(1), 2, true, Some("Ready(1)"))
+        );
+
+        // A new future at the address of the completed one
+        let (future, _) = tree.poll(2, 5, &locals("fetch", 200, 1)).unwrap();
+        assert_eq!(summary(&future), (3, "fetch", Some(1), 1, false, None));
+        assert_eq!(tree.frame_end(2, 5, Some(("Pending", "Pending"))), None);
+        assert_eq!(tree.frame_end(2, 3, Some(("Pending", "Pending"))), None);
+        assert!(tree.polls.values().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn test_async_tree_reused_address() {
+        let mut tree = AsyncTree::default();
+        let (future, _) = tree.poll(1, 1, &locals("fetch", 100, 1)).unwrap();
+        assert_eq!(future.id, 1);
+        tree.frame_end(1, 1, Some(("Pending", "Pending")));
+
+        // `fetch` is dropped before completion; its address is reused by another function
+        let (future, _) = tree.poll(1, 2, &locals("store", 100, 1)).unwrap();
+        assert_eq!(summary(&future), (2, "store", None, 1, false, None));
+        tree.frame_end(1, 2, Some(("Pending", "Pending")));
+
+        // Then by another future of the same function, as the poll count restarts
+        let (future, _) = tree.poll(1, 3, &locals("store", 100, 2)).unwrap();
+        assert_eq!(future.id, 2);
+        tree.frame_end(1, 3, Some(("Pending", "Pending")));
+        let (future, _) = tree.poll(1, 4, &locals("store", 100, 1)).unwrap();
+        assert_eq!(summary(&future), (3, "store", None, 1, false, None));
+        tree.frame_end(1, 4, Some(("Pending", "Pending")));
+        assert_eq!(tree.pending.len(), 1);
+    }
+
+    #[test]
+    fn test_async_tree_unwind() {
+        let mut tree = AsyncTree::default();
+        tree.poll(1, 1, &locals("main", 100, 1)).unwrap();
+        tree.poll(1, 2, &locals("fetch", 200, 1)).unwrap();
+        // The poll of `fetch` panicked
+        assert_eq!(tree.frame_end(1, 2, None), None);
+        assert_eq!(tree.pending.len(), 1);
+        // The next poll at the address is a new future, even with a greater poll count
+        let (future, _) = tree.poll(1, 3, &locals("fetch", 200, 2)).unwrap();
+        assert_eq!(summary(&future), (3, "fetch", Some(1), 2, false, None));
+        assert_eq!(tree.pending.len(), 2);
+    }
+}
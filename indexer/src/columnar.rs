@@ -0,0 +1,314 @@
+//! Write the indexed tables to Parquet files, with the same columns as the SeaORM entities.
+//!
+//! Each table is written to `<dir>/<table>.parquet`, which can be queried by DuckDB, Polars,
+//! Pandas, Spark etc, e.g. `SELECT * FROM 'output/event.parquet'`.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray,
+    TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use sea_orm::{
+    sea_query::ColumnType, ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, IdenStatic,
+    Iterable, PrimaryKeyToColumn, Value,
+};
+
+use crate::entity::{
+    allocation::{self, Entity as Allocation},
+    breakpoint::{self, Entity as Breakpoint},
+    event::{self, Entity as Event},
+    file::{self, Entity as File},
+    value::{self, Entity as ValueEntity},
+};
+
+const TIMEZONE: &str = "+00:00";
+
+#[derive(Debug)]
+pub struct ParquetWriter {
+    files: Table<File>,
+    events: Table<Event>,
+    allocations: Table<Allocation>,
+    /// Only with `--values`
+    values: Option<Table<ValueEntity>>,
+    /// Written on close, after the hit counts are known
+    breakpoints: Vec<breakpoint::ActiveModel>,
+    hit_counts: HashMap<i64, i64>,
+    dir: PathBuf,
+}
+
+impl ParquetWriter {
+    /// Create the directory `dir` and the Parquet files in it, overwriting existing ones;
+    /// `value.parquet` is only created if `values`
+    pub fn create(dir: PathBuf, values: bool) -> Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Fail to create directory: `{}`", dir.display()))?;
+        Ok(Self {
+            files: Table::create(&dir, File)?,
+            events: Table::create(&dir, Event)?,
+            allocations: Table::create(&dir, Allocation)?,
+            values: match values {
+                true => Some(Table::create(&dir, ValueEntity)?),
+                false => None,
+            },
+            breakpoints: Vec::new(),
+            hit_counts: Default::default(),
+            dir,
+        })
+    }
+
+    pub fn write_files(&mut self, files: Vec<file::ActiveModel>) -> Result<()> {
+        self.files.write(files)
+    }
+
+    pub fn write_breakpoints(&mut self, breakpoints: Vec<breakpoint::ActiveModel>) {
+        self.breakpoints.extend(breakpoints);
+    }
+
+    pub fn write_events(&mut self, events: Vec<event::ActiveModel>) -> Result<()> {
+        for event in events.iter() {
+            if let ActiveValue::Set(bp_id) = &event.breakpoint_id {
+                *self.hit_counts.entry(*bp_id).or_default() += 1;
+            }
+        }
+        self.events.write(events)
+    }
+
+    pub fn write_allocations(&mut self, allocations: Vec<allocation::ActiveModel>) -> Result<()> {
+        self.allocations.write(allocations)
+    }
+
+    pub fn write_values(&mut self, values: Vec<value::ActiveModel>) -> Result<()> {
+        match &mut self.values {
+            Some(table) => table.write(values),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the breakpoints and the file footers; the files are not readable before this
+    pub fn close(self) -> Result<()> {
+        let Self {
+            files,
+            events,
+            allocations,
+            values,
+            mut breakpoints,
+            hit_counts,
+            dir,
+        } = self;
+        for bp in breakpoints.iter_mut() {
+            if let ActiveValue::Set(bp_id) = &bp.id {
+                let hit_count = hit_counts.get(bp_id).copied().unwrap_or_default();
+                bp.hit_count = ActiveValue::Set(hit_count);
+            }
+        }
+        let mut table = Table::create(&dir, Breakpoint)?;
+        table.write(breakpoints)?;
+        table.close()?;
+        files.close()?;
+        events.close()?;
+        allocations.close()?;
+        if let Some(values) = values {
+            values.close()?;
+        }
+        Ok(())
+    }
+}
+
+/// A Parquet file of the entity `E`
+struct Table<E: EntityTrait> {
+    columns: Vec<E::Column>,
+    schema: SchemaRef,
+    writer: ArrowWriter<std::fs::File>,
+    /// Integer primary keys not set are assigned in sequence, like `AUTOINCREMENT`
+    auto_increment: Option<usize>,
+    /// A directory holds a single run, so `run_id` is always 1
+    run_id: Option<usize>,
+    count: i64,
+}
+
+impl<E: EntityTrait> std::fmt::Debug for Table<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Table")
+            .field("schema", &self.schema)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl<E: EntityTrait> Table<E> {
+    fn create(dir: &Path, entity: E) -> Result<Self> {
+        let columns: Vec<E::Column> = E::Column::iter().collect();
+        let schema: SchemaRef = Arc::new(Schema::new(
+            columns
+                .iter()
+                .map(|column| {
+                    let def = column.def();
+                    Field::new(
+                        column.as_str(),
+                        data_type(def.get_column_type()),
+                        def.is_null(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        ));
+        let mut primary_key = E::PrimaryKey::iter().map(|key| key.into_column());
+        // Only a single column key is auto-incremented
+        let primary_key = primary_key.next().filter(|_| primary_key.next().is_none());
+        let auto_increment = columns.iter().position(|column| {
+            primary_key.is_some_and(|key| key.as_str() == column.as_str())
+                && data_type(column.def().get_column_type()) == DataType::Int64
+        });
+        let run_id = columns
+            .iter()
+            .position(|column| column.as_str() == "run_id");
+
+        let path = dir.join(format!("{}.parquet", entity.table_name()));
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Fail to create file: `{}`", path.display()))?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))
+            .context("Fail to create Parquet writer")?;
+
+        Ok(Self {
+            columns,
+            schema,
+            writer,
+            auto_increment,
+            run_id,
+            count: 0,
+        })
+    }
+
+    /// Rows are buffered into row groups by the writer
+    fn write<A: ActiveModelTrait<Entity = E>>(&mut self, rows: Vec<A>) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut cells: Vec<Vec<Option<Value>>> =
+            vec![Vec::with_capacity(rows.len()); self.columns.len()];
+        for row in rows {
+            self.count += 1;
+            for (i, column) in self.columns.iter().enumerate() {
+                let value = match row.get(*column) {
+                    ActiveValue::Set(value) | ActiveValue::Unchanged(value) => Some(value),
+                    ActiveValue::NotSet if Some(i) == self.auto_increment => {
+                        Some(Value::BigInt(Some(self.count)))
+                    }
+                    ActiveValue::NotSet if Some(i) == self.run_id => Some(Value::BigInt(Some(1))),
+                    ActiveValue::NotSet => None,
+                };
+                cells[i].push(value);
+            }
+        }
+        let arrays: Vec<ArrayRef> = self
+            .schema
+            .fields()
+            .iter()
+            .zip(cells)
+            .map(|(field, cells)| array(field.data_type(), cells))
+            .collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)
+            .context("Fail to build record batch")?;
+        self.writer
+            .write(&batch)
+            .context("Fail to write Parquet file")?;
+        Ok(())
+    }
+
+    fn close(self) -> Result<()> {
+        self.writer.close().context("Fail to close Parquet file")?;
+        Ok(())
+    }
+}
+
+/// Integers are widened to `Int64` as in SQLite
+fn data_type(column_type: &ColumnType) -> DataType {
+    match column_type {
+        ColumnType::TinyInteger
+        | ColumnType::SmallInteger
+        | ColumnType::Integer
+        | ColumnType::BigInteger
+        | ColumnType::TinyUnsigned
+        | ColumnType::SmallUnsigned
+        | ColumnType::Unsigned
+        | ColumnType::BigUnsigned => DataType::Int64,
+        ColumnType::Float | ColumnType::Double => DataType::Float64,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Timestamp | ColumnType::TimestampWithTimeZone => {
+            DataType::Timestamp(TimeUnit::Microsecond, Some(TIMEZONE.into()))
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+fn array(data_type: &DataType, cells: Vec<Option<Value>>) -> ArrayRef {
+    let cells = cells.into_iter();
+    match data_type {
+        DataType::Int64 => Arc::new(cells.map(|v| v.and_then(as_i64)).collect::<Int64Array>()),
+        DataType::Float64 => Arc::new(cells.map(|v| v.and_then(as_f64)).collect::<Float64Array>()),
+        DataType::Boolean => Arc::new(
+            cells
+                .map(|v| match v {
+                    Some(Value::Bool(v)) => v,
+                    _ => None,
+                })
+                .collect::<BooleanArray>(),
+        ),
+        DataType::Timestamp(..) => Arc::new(
+            cells
+                .map(|v| match v {
+                    Some(Value::TimeDateTimeWithTimeZone(Some(v))) => {
+                        Some((v.unix_timestamp_nanos() / 1000) as i64)
+                    }
+                    _ => None,
+                })
+                .collect::<TimestampMicrosecondArray>()
+                .with_timezone(TIMEZONE),
+        ),
+        _ => Arc::new(
+            cells
+                .map(|v| v.and_then(as_string))
+                .collect::<StringArray>(),
+        ),
+    }
+}
+
+fn as_i64(value: Value) -> Option<i64> {
+    match value {
+        Value::TinyInt(v) => v.map(i64::from),
+        Value::SmallInt(v) => v.map(i64::from),
+        Value::Int(v) => v.map(i64::from),
+        Value::BigInt(v) => v,
+        Value::TinyUnsigned(v) => v.map(i64::from),
+        Value::SmallUnsigned(v) => v.map(i64::from),
+        Value::Unsigned(v) => v.map(i64::from),
+        Value::BigUnsigned(v) => v.map(|v| v as i64),
+        _ => None,
+    }
+}
+
+fn as_f64(value: Value) -> Option<f64> {
+    match value {
+        Value::Float(v) => v.map(f64::from),
+        Value::Double(v) => v,
+        _ => None,
+    }
+}
+
+fn as_string(value: Value) -> Option<String> {
+    match value {
+        Value::String(v) => v.map(|v| *v),
+        Value::Char(v) => v.map(String::from),
+        Value::Json(v) => v.map(|v| v.to_string()),
+        _ => None,
+    }
+}
@@ -4,15 +4,35 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "allocation")]
 pub struct Model {
-    #[sea_orm(primary_key, column_type = "Integer")]
+    #[sea_orm(primary_key, column_type = "BigInteger")]
     pub id: i64,
+    #[sea_orm(indexed)]
+    pub run_id: i64,
     pub action: String,
     /// Sadly SQLite does not support u64
     pub address: i64,
+    /// Size in bytes; 0 if unknown
+    pub size: i64,
+    /// Alignment in bytes; 0 if unknown
+    pub align: i64,
+    /// The address being reallocated; only for `Realloc`
+    pub old_address: Option<i64>,
+    pub thread_id: i64,
+    /// The innermost recorded frame making the allocation; join with `event` on (`thread_id`, `frame_id`)
+    #[sea_orm(indexed)]
+    pub frame_id: Option<i64>,
+    /// Empty if the allocation is not made by `Box` / `Rc` / `Arc`
     pub type_name: String,
 }
 
 #[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
 
 impl ActiveModelBehavior for ActiveModel {}
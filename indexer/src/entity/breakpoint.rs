@@ -4,11 +4,14 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "breakpoint")]
 pub struct Model {
-    #[sea_orm(primary_key, auto_increment = false, column_type = "Integer")]
-    pub id: u32,
-    pub file_id: u32,
-    pub loc_line: u32,
-    pub loc_column: Option<u32>,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub run_id: i64,
+    /// Sadly PostgreSQL does not support u32
+    #[sea_orm(primary_key, auto_increment = false, column_type = "BigInteger")]
+    pub id: i64,
+    pub file_id: i64,
+    pub loc_line: i64,
+    pub loc_column: Option<i64>,
     pub breakpoint_type: String,
     pub capture: String,
     pub hit_count: i64,
@@ -16,10 +19,16 @@ pub struct Model {
 
 #[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
     #[sea_orm(
         belongs_to = "super::file::Entity",
-        from = "Column::FileId",
-        to = "super::file::Column::Id"
+        from = "(Column::RunId, Column::FileId)",
+        to = "(super::file::Column::RunId, super::file::Column::Id)"
     )]
     File,
 }
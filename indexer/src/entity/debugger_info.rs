@@ -4,8 +4,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "debugger_info")]
 pub struct Model {
-    #[sea_orm(primary_key)]
-    pub id: u32,
+    /// One row per run
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub run_id: i64,
     pub debugger: String,
     pub version: String,
     pub workspace_root: String,
@@ -13,10 +14,29 @@ pub struct Model {
     pub target: String,
     /// Json
     pub arguments: String,
+    /// Json object of the environment variables set
+    pub env: String,
+    /// Json array of the environment variables removed
+    pub env_remove: String,
+    pub cwd: Option<String>,
     pub exit_code: Option<i32>,
+    /// Number of the signal that terminated the program
+    pub exit_signal: Option<i32>,
+    /// e.g. `SIGSEGV`
+    pub exit_signal_name: Option<String>,
+    pub exit_description: Option<String>,
+    /// Json array of the backtrace of every thread at the time the program crashed
+    pub exit_backtrace: Option<String>,
 }
 
 #[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
 
 impl ActiveModelBehavior for ActiveModel {}
@@ -4,9 +4,13 @@ use serde::Serialize;
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "event")]
 pub struct Model {
-    #[sea_orm(primary_key, column_type = "Integer")]
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub run_id: i64,
+    /// In the order of the recording, from 1
+    #[sea_orm(primary_key, auto_increment = false, column_type = "BigInteger")]
     pub id: i64,
-    pub breakpoint_id: u32,
+    /// Sadly PostgreSQL does not support u32
+    pub breakpoint_id: i64,
     /// Sadly SQLite does not support u64
     pub thread_id: i64,
     #[sea_orm(indexed)]
@@ -22,14 +26,28 @@ pub struct Model {
     pub pretty: String,
     /// If any local, argument or return value is of `Err` type
     pub is_error: bool,
+    /// Nanoseconds the target has been running, excluding the time stopped by the debugger; only for `F` and `R` events
+    pub clock: Option<i64>,
+    /// CPU time of the thread in nanoseconds; only for `F` and `R` events, if recorded
+    pub cpu_time: Option<i64>,
+    /// Duration of the frame including its callees in nanoseconds; only for `R`, `U` and `I` events
+    pub inclusive_time: Option<i64>,
+    /// Duration of the frame excluding its callees in nanoseconds; only for `R`, `U` and `I` events
+    pub exclusive_time: Option<i64>,
 }
 
 #[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
     #[sea_orm(
         belongs_to = "super::breakpoint::Entity",
-        from = "Column::BreakpointId",
-        to = "super::breakpoint::Column::Id"
+        from = "(Column::RunId, Column::BreakpointId)",
+        to = "(super::breakpoint::Column::RunId, super::breakpoint::Column::Id)"
     )]
     Breakpoint,
 }
@@ -45,7 +63,7 @@ impl ActiveModelBehavior for ActiveModel {}
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, DeriveDisplay)]
 #[sea_orm(
     rs_type = "String",
-    db_type = "String(Some(1))",
+    db_type = "String(Some(2))",
     enum_name = "event_type"
 )]
 pub enum EventType {
@@ -57,10 +75,23 @@ pub enum EventType {
     FunctionCall,
     #[sea_orm(string_value = "R")]
     FunctionReturn,
+    #[sea_orm(string_value = "U")]
+    FunctionUnwind,
+    /// The recording ended before the function returned
+    #[sea_orm(string_value = "I")]
+    Incomplete,
     #[sea_orm(string_value = "AF")]
     FutureEnter,
+    #[sea_orm(string_value = "AP")]
+    FuturePoll,
     #[sea_orm(string_value = "AR")]
     FutureExit,
+    #[sea_orm(string_value = "T")]
+    Truncated,
+    #[sea_orm(string_value = "W")]
+    Watch,
+    #[sea_orm(string_value = "C")]
+    Catch,
 }
 
 impl Serialize for EventType {
@@ -4,14 +4,24 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "file")]
 pub struct Model {
-    #[sea_orm(primary_key, auto_increment = false, column_type = "Integer")]
-    pub id: u32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub run_id: i64,
+    /// Sadly PostgreSQL does not support u32
+    #[sea_orm(primary_key, auto_increment = false, column_type = "BigInteger")]
+    pub id: i64,
     pub path: String,
     pub crate_name: String,
     pub modified: String,
 }
 
 #[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
 
 impl ActiveModelBehavior for ActiveModel {}
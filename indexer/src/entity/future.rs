@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "future")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub run_id: i64,
+    /// Assigned in the order the futures are first polled
+    #[sea_orm(primary_key, auto_increment = false, column_type = "BigInteger")]
+    pub id: i64,
+    /// Address of the generator state; it may be reused once the future has completed
+    pub address: i64,
+    /// The `async fn`
+    pub function_name: String,
+    /// The future which first polled this one, i.e. awaited it; `None` for a task polled by the executor
+    #[sea_orm(indexed)]
+    pub parent_id: Option<i64>,
+    /// The first poll; join with the `F` event on (`thread_id`, `frame_id`)
+    pub thread_id: i64,
+    pub frame_id: i64,
+    /// Number of polls so far
+    pub polls: i64,
+    /// Whether a poll has returned `Poll::Ready`
+    pub ready: bool,
+    /// Json of the return value of the last poll, i.e. `Poll::Ready(..)`
+    pub result: Option<String>,
+    /// A pretty printed version of result
+    pub pretty: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
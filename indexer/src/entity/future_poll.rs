@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "future_poll")]
+pub struct Model {
+    #[sea_orm(primary_key, column_type = "BigInteger")]
+    pub id: i64,
+    #[sea_orm(indexed)]
+    pub run_id: i64,
+    #[sea_orm(indexed)]
+    pub future_id: i64,
+    /// Counting from 1
+    pub poll: i64,
+    /// The thread polling the future, which can differ from poll to poll on a multi-threaded executor
+    pub thread_id: i64,
+    /// The frame of this poll; join with the `F` event on (`thread_id`, `frame_id`)
+    pub frame_id: i64,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
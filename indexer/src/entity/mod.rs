@@ -4,4 +4,11 @@ pub mod debugger_info;
 pub mod event;
 pub mod file;
 pub mod function;
+pub mod future;
+pub mod future_poll;
+pub mod output;
+pub mod panic;
+pub mod run;
 pub mod type_info;
+pub mod value;
+pub mod warning;
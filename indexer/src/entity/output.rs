@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "output")]
+pub struct Model {
+    #[sea_orm(primary_key, column_type = "BigInteger")]
+    pub id: i64,
+    #[sea_orm(indexed)]
+    pub run_id: i64,
+    /// `Stdin`, `Stdout` or `Stderr`
+    pub channel: String,
+    /// The thread stopped right after the chunk was written; 0 if the program has exited
+    pub thread_id: i64,
+    /// The innermost recorded frame of that thread; join with `event` on (`thread_id`, `frame_id`)
+    #[sea_orm(indexed)]
+    pub frame_id: Option<i64>,
+    pub timestamp: TimeDateTimeWithTimeZone,
+    /// Nanoseconds the target has been running, comparable to `event.clock`
+    #[sea_orm(indexed)]
+    pub clock: i64,
+    pub content: String,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
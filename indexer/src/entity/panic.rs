@@ -0,0 +1,36 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "panic")]
+pub struct Model {
+    #[sea_orm(primary_key, column_type = "BigInteger")]
+    pub id: i64,
+    #[sea_orm(indexed)]
+    pub run_id: i64,
+    pub thread_id: i64,
+    /// The innermost recorded frame; join with the `P` event on (`thread_id`, `frame_id`)
+    #[sea_orm(indexed)]
+    pub frame_id: Option<i64>,
+    pub timestamp: TimeDateTimeWithTimeZone,
+    pub message: Option<String>,
+    /// Source location of `panic!`
+    #[sea_orm(indexed)]
+    pub file: Option<String>,
+    pub line: Option<i64>,
+    pub column: Option<i64>,
+    /// Json array of the native backtrace, innermost frame first
+    pub backtrace: String,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A node of the locals, arguments or return value of an event; only indexed with `--values`
+#[derive(Debug, Clone, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "value")]
+pub struct Model {
+    #[sea_orm(primary_key, column_type = "BigInteger")]
+    pub id: i64,
+    pub run_id: i64,
+    pub event_id: i64,
+    /// e.g. `args.0.items[3].name`, `return.Err` or `locals.1.Some`; references are followed
+    /// without adding to the path
+    pub path: String,
+    pub type_name: String,
+    /// The primitive value, string content (up to 256 chars), or the variant of an enum, `Option`
+    /// or `Result`
+    pub value: Option<String>,
+    /// The numeric value, the number of chars of a string, or the length of an array or bytes
+    pub number: Option<f64>,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+    #[sea_orm(
+        belongs_to = "super::event::Entity",
+        from = "(Column::RunId, Column::EventId)",
+        to = "(super::event::Column::RunId, super::event::Column::Id)"
+    )]
+    Event,
+}
+
+impl Related<super::event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Event.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
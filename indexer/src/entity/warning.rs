@@ -0,0 +1,43 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A message which cannot be decoded was skipped
+pub const UNDECODABLE: &str = "undecodable";
+/// A message of an unexpected stream was skipped
+pub const UNKNOWN_STREAM: &str = "unknown_stream";
+/// A function returned or unwound without a call
+pub const UNMATCHED_RETURN: &str = "unmatched_return";
+/// A function has neither returned nor unwound; an `I` event is added
+pub const INCOMPLETE_FRAME: &str = "incomplete_frame";
+
+#[derive(Debug, Clone, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "warnings")]
+pub struct Model {
+    #[sea_orm(primary_key, column_type = "BigInteger")]
+    pub id: i64,
+    #[sea_orm(indexed)]
+    pub run_id: i64,
+    /// One of [`UNDECODABLE`], [`UNKNOWN_STREAM`], [`UNMATCHED_RETURN`] or [`INCOMPLETE_FRAME`]
+    #[sea_orm(indexed)]
+    pub kind: String,
+    /// The stream the offending message came from
+    pub stream: String,
+    pub thread_id: Option<i64>,
+    /// Join with `event` on (`thread_id`, `frame_id`)
+    pub frame_id: Option<i64>,
+    pub timestamp: TimeDateTimeWithTimeZone,
+    /// What was wrong and how it was repaired
+    pub message: String,
+}
+
+#[derive(Debug, Copy, Clone, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::run::Entity",
+        from = "Column::RunId",
+        to = "super::run::Column::Id"
+    )]
+    Run,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
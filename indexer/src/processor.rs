@@ -6,7 +6,13 @@ use sea_streamer::SharedMessage;
 pub trait Processor {
     async fn batch(&mut self, messages: impl Iterator<Item = SharedMessage> + Send) -> Result<()>;
 
+    /// No message is buffered; a chance to flush what has been processed so far
+    async fn idle(&mut self) -> Result<()>;
+
     async fn end(&mut self) -> Result<()>;
 
+    /// The processing failed halfway; undo what has been written, if needed
+    async fn abort(&mut self) -> Result<()>;
+
     fn finish(self) -> Result<()>;
 }
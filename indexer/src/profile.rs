@@ -0,0 +1,312 @@
+//! Export the timing of function calls as a flame graph profile
+use crate::{
+    database::Database,
+    entity::event::{self, EventType},
+};
+use anyhow::{Context, Result};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde_json::json;
+use std::{collections::BTreeMap, fmt::Write, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// Folded stacks, one line per stack, e.g. `main;run;sort 1200`; the value is the exclusive time in nanoseconds
+    Folded,
+    /// <https://www.speedscope.app> evented profile
+    Speedscope,
+}
+
+impl FromStr for ProfileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "folded" => Ok(Self::Folded),
+            "speedscope" => Ok(Self::Speedscope),
+            _ => Err(format!(
+                "Unknown profile format `{s}`; expected `folded` or `speedscope`"
+            )),
+        }
+    }
+}
+
+/// (thread_id, frame_id, function_name, event_type, clock, exclusive_time)
+type FrameEvent = (
+    i64,
+    i64,
+    Option<String>,
+    EventType,
+    Option<i64>,
+    Option<i64>,
+);
+
+/// (opened frames as (frame_id, frame index), timeline, start, end) of a thread
+type Timeline = (Vec<(i64, usize)>, Vec<serde_json::Value>, i64, i64);
+
+/// Export the profile of an indexed run to the `path`
+pub async fn export(db: &Database, format: ProfileFormat, path: &str) -> Result<()> {
+    let events: Vec<FrameEvent> = event::Entity::find()
+        .select_only()
+        .columns([
+            event::Column::ThreadId,
+            event::Column::FrameId,
+            event::Column::FunctionName,
+            event::Column::EventType,
+            event::Column::Clock,
+            event::Column::ExclusiveTime,
+        ])
+        .filter(event::Column::EventType.is_in([
+            EventType::FunctionCall,
+            EventType::FunctionReturn,
+            EventType::FunctionUnwind,
+            EventType::Incomplete,
+        ]))
+        .order_by_asc(event::Column::Id)
+        .into_tuple()
+        .all(db.db())
+        .await
+        .context("Fail to select events")?;
+
+    if events.iter().all(|(_, _, _, _, clock, _)| clock.is_none()) {
+        anyhow::bail!("No timing is recorded in this run");
+    }
+
+    let content = match format {
+        ProfileFormat::Folded => folded(events),
+        ProfileFormat::Speedscope => speedscope(events)?,
+    };
+    std::fs::write(path, content).with_context(|| format!("Fail to write file: `{path}`"))?;
+
+    Ok(())
+}
+
+fn folded(events: Vec<FrameEvent>) -> String {
+    // thread id -> (frame id, function name)[]
+    let mut stacks: BTreeMap<i64, Vec<(i64, String)>> = Default::default();
+    // stack -> exclusive time
+    let mut samples: BTreeMap<String, i64> = Default::default();
+
+    for (thread_id, frame_id, function_name, event_type, _, exclusive_time) in events {
+        let stack = stacks.entry(thread_id).or_default();
+        match event_type {
+            EventType::FunctionCall => {
+                stack.push((frame_id, function_name.unwrap_or_default()));
+            }
+            EventType::FunctionReturn | EventType::FunctionUnwind | EventType::Incomplete => {
+                if !matches!(stack.last(), Some((id, _)) if *id == frame_id) {
+                    continue;
+                }
+                if let Some(exclusive_time) = exclusive_time {
+                    let key = stack
+                        .iter()
+                        .map(|(_, name)| name.replace(';', ":"))
+                        .collect::<Vec<_>>()
+                        .join(";");
+                    *samples.entry(key).or_default() += exclusive_time;
+                }
+                stack.pop();
+            }
+            _ => (),
+        }
+    }
+
+    let mut string = String::new();
+    for (stack, time) in samples {
+        writeln!(string, "{stack} {time}").expect("Write to String");
+    }
+    string
+}
+
+fn speedscope(events: Vec<FrameEvent>) -> Result<String> {
+    let mut frames: Vec<String> = Vec::new();
+    let mut frame_index: BTreeMap<String, usize> = Default::default();
+    // thread id -> timeline
+    let mut threads: BTreeMap<i64, Timeline> = Default::default();
+
+    for (thread_id, frame_id, function_name, event_type, clock, _) in events {
+        let Some(clock) = clock else {
+            continue;
+        };
+        let (stack, timeline, start, end) = threads
+            .entry(thread_id)
+            .or_insert_with(|| (Vec::new(), Vec::new(), clock, clock));
+        *end = clock;
+        match event_type {
+            EventType::FunctionCall => {
+                let name = function_name.unwrap_or_default();
+                let index = *frame_index.entry(name.clone()).or_insert_with(|| {
+                    frames.push(name);
+                    frames.len() - 1
+                });
+                stack.push((frame_id, index));
+                timeline.push(json!({ "type": "O", "frame": index, "at": clock - *start }));
+            }
+            EventType::FunctionReturn | EventType::FunctionUnwind | EventType::Incomplete => {
+                if let Some((_, index)) = stack.last().filter(|(id, _)| *id == frame_id) {
+                    timeline.push(json!({ "type": "C", "frame": index, "at": clock - *start }));
+                    stack.pop();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let profiles: Vec<_> = threads
+        .into_iter()
+        .map(|(thread_id, (stack, mut timeline, start, end))| {
+            // Close the frames that never return
+            for (_, index) in stack.into_iter().rev() {
+                timeline.push(json!({ "type": "C", "frame": index, "at": end - start }));
+            }
+            json!({
+                "type": "evented",
+                "name": format!("Thread {thread_id}"),
+                "unit": "nanoseconds",
+                "startValue": 0,
+                "endValue": end - start,
+                "events": timeline,
+            })
+        })
+        .collect();
+
+    let profile = json!({
+        "$schema": "https://www.speedscope.app/file-format-schema.json",
+        "exporter": "firedbg",
+        "shared": {
+            "frames": frames.into_iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        },
+        "profiles": profiles,
+    });
+
+    serde_json::to_string(&profile).context("Fail to serialize JSON")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn call(thread_id: i64, frame_id: i64, name: &str, clock: i64) -> FrameEvent {
+        (
+            thread_id,
+            frame_id,
+            Some(name.to_owned()),
+            EventType::FunctionCall,
+            Some(clock),
+            None,
+        )
+    }
+
+    fn ret(thread_id: i64, frame_id: i64, clock: i64, exclusive_time: i64) -> FrameEvent {
+        (
+            thread_id,
+            frame_id,
+            None,
+            EventType::FunctionReturn,
+            Some(clock),
+            Some(exclusive_time),
+        )
+    }
+
+    #[test]
+    fn test_folded() {
+        let events = vec![
+            call(1, 1, "main", 0),
+            call(1, 2, "a", 10),
+            call(1, 3, "b", 20),
+            ret(1, 3, 25, 5),
+            ret(1, 2, 30, 15),
+            call(1, 4, "c", 30),
+            ret(1, 4, 33, 3),
+            ret(1, 1, 40, 10),
+        ];
+        assert_eq!(folded(events), "main 10\nmain;a 15\nmain;a;b 5\nmain;c 3\n");
+    }
+
+    #[test]
+    fn test_folded_unmatched() {
+        let events = vec![
+            call(1, 1, "main", 0),
+            call(1, 2, "a", 10),
+            // Not the innermost frame
+            ret(1, 1, 15, 15),
+            // Never called
+            ret(1, 9, 15, 15),
+            ret(1, 2, 20, 10),
+            // `main` never returns
+        ];
+        assert_eq!(folded(events), "main;a 10\n");
+    }
+
+    #[test]
+    fn test_folded_threads() {
+        let events = vec![
+            call(1, 1, "main", 0),
+            call(2, 1, "worker", 5),
+            call(1, 2, "a", 10),
+            call(2, 2, "a", 10),
+            ret(2, 2, 12, 2),
+            ret(1, 2, 14, 4),
+            ret(2, 1, 20, 13),
+            ret(1, 1, 30, 26),
+        ];
+        assert_eq!(folded(events), "main 26\nmain;a 4\nworker 13\nworker;a 2\n");
+    }
+
+    #[test]
+    fn test_speedscope() -> Result<()> {
+        let events = vec![
+            call(1, 1, "main", 100),
+            call(1, 2, "a", 110),
+            call(2, 1, "b", 200),
+            ret(1, 2, 130, 20),
+            // `main` never returns; closed at the end of the thread
+            // Never called
+            ret(2, 9, 210, 0),
+            // No clock recorded
+            (
+                1,
+                3,
+                Some("c".to_owned()),
+                EventType::FunctionCall,
+                None,
+                None,
+            ),
+        ];
+        let profile: serde_json::Value = serde_json::from_str(&speedscope(events)?)?;
+        assert_eq!(
+            profile["shared"]["frames"],
+            json!([{ "name": "main" }, { "name": "a" }, { "name": "b" }])
+        );
+        assert_eq!(
+            profile["profiles"],
+            json!([
+                {
+                    "type": "evented",
+                    "name": "Thread 1",
+                    "unit": "nanoseconds",
+                    "startValue": 0,
+                    "endValue": 30,
+                    "events": [
+                        { "type": "O", "frame": 0, "at": 0 },
+                        { "type": "O", "frame": 1, "at": 10 },
+                        { "type": "C", "frame": 1, "at": 30 },
+                        { "type": "C", "frame": 0, "at": 30 },
+                    ],
+                },
+                {
+                    "type": "evented",
+                    "name": "Thread 2",
+                    "unit": "nanoseconds",
+                    "startValue": 0,
+                    "endValue": 10,
+                    "events": [
+                        { "type": "O", "frame": 2, "at": 0 },
+                        { "type": "C", "frame": 2, "at": 10 },
+                    ],
+                },
+            ])
+        );
+        Ok(())
+    }
+}
@@ -1,21 +1,28 @@
 use crate::entity::{
     allocation::ActiveModel as Allocation,
-    breakpoint::{self, Model as Breakpoint},
+    breakpoint::ActiveModel as Breakpoint,
     debugger_info::ActiveModel as DebuggerInfo,
     event::{ActiveModel as Event, EventType},
     file::ActiveModel as File,
-    type_info::Model as TypeInfo,
+    output::ActiveModel as Output,
+    panic::ActiveModel as Panic,
+    type_info::ActiveModel as TypeInfo,
+    value::ActiveModel as Value,
+    warning::ActiveModel as Warning,
 };
 use firedbg_rust_debugger::{
     Allocation as SrcAllocation, Breakpoint as SrcBreakPoint, DebuggerInfo as SrcDebuggerInfo,
-    Event as SrcEvent, InfoMessage as SrcInfoMessage, ProgExitInfo as SrcProgExitInfo, RValue,
-    Reason, SourceFile,
+    Event as SrcEvent, InfoMessage as SrcInfoMessage, Output as SrcOutput, PValue,
+    Panic as SrcPanic, ProgExitInfo as SrcProgExitInfo, RValue, Reason, SourceFile,
 };
-use sea_orm::{prelude::DateTimeUtc, IntoActiveModel, NotSet, Set};
+use sea_orm::{prelude::DateTimeUtc, NotSet, Set};
 use sea_streamer::Timestamp;
 use serde::Serialize;
 use std::fmt::Write;
 
+/// Longer strings are truncated in the `value` table, to be indexable; `event.data` has them in full
+const VALUE_MAX_CHARS: usize = 256;
+
 pub fn debugger_info(info: SrcInfoMessage) -> DebuggerInfo {
     match info {
         SrcInfoMessage::Debugger(SrcDebuggerInfo {
@@ -25,19 +32,44 @@ pub fn debugger_info(info: SrcInfoMessage) -> DebuggerInfo {
             package_name,
             target,
             arguments,
+            env,
+            env_remove,
+            cwd,
         }) => DebuggerInfo {
-            id: NotSet,
+            run_id: NotSet,
             debugger: Set(debugger.to_string()),
             version: Set(version),
             workspace_root: Set(workspace_root),
             package_name: Set(package_name),
             target: Set(target),
             arguments: Set(json_stringify(&arguments)),
+            env: Set(json_stringify(&env)),
+            env_remove: Set(json_stringify(&env_remove)),
+            cwd: Set(cwd),
             exit_code: Set(None),
+            exit_signal: Set(None),
+            exit_signal_name: Set(None),
+            exit_description: Set(None),
+            exit_backtrace: Set(None),
         },
-        SrcInfoMessage::Exit(SrcProgExitInfo { exit_code }) => DebuggerInfo {
-            id: Set(1),
-            exit_code: Set(Some(exit_code)),
+        SrcInfoMessage::Exit(SrcProgExitInfo {
+            exit_code,
+            detached,
+            signal,
+            exit_description,
+            backtrace,
+        }) => DebuggerInfo {
+            // The row of the run is updated
+            run_id: NotSet,
+            exit_code: Set(if detached { None } else { Some(exit_code) }),
+            exit_signal: Set(signal.as_ref().map(|signal| signal.number)),
+            exit_signal_name: Set(signal.map(|signal| signal.name)),
+            exit_description: Set(exit_description),
+            exit_backtrace: Set(if backtrace.is_empty() {
+                None
+            } else {
+                Some(json_stringify(&backtrace))
+            }),
             ..Default::default()
         },
     }
@@ -52,14 +84,15 @@ pub fn source_file(f: SourceFile) -> File {
     } = f;
     let modified: DateTimeUtc = modified.into();
     File {
-        id: Set(id),
+        run_id: NotSet,
+        id: Set(id as i64),
         path: Set(path),
         crate_name: Set(crate_name),
         modified: Set(modified.format("%Y-%m-%d %H:%M:%S").to_string()),
     }
 }
 
-pub fn breakpoint(bp: SrcBreakPoint) -> breakpoint::ActiveModel {
+pub fn breakpoint(bp: SrcBreakPoint) -> Breakpoint {
     let SrcBreakPoint {
         id,
         file_id,
@@ -70,15 +103,15 @@ pub fn breakpoint(bp: SrcBreakPoint) -> breakpoint::ActiveModel {
     } = bp;
 
     Breakpoint {
-        id,
-        file_id,
-        loc_line: loc.line,
-        loc_column: loc.column,
-        breakpoint_type: json_stringify(&bp_type),
-        capture: json_stringify(&capture),
-        hit_count: 0,
+        run_id: NotSet,
+        id: Set(id as i64),
+        file_id: Set(file_id as i64),
+        loc_line: Set(loc.line as i64),
+        loc_column: Set(loc.column.map(|column| column as i64)),
+        breakpoint_type: Set(json_stringify(&bp_type)),
+        capture: Set(json_stringify(&capture)),
+        hit_count: Set(0),
     }
-    .into_active_model()
 }
 
 pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
@@ -107,7 +140,11 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
             let event_type = match reason {
                 Reason::Breakpoint => EventType::Breakpoint,
                 Reason::FutureEnter => EventType::FutureEnter,
+                Reason::FuturePoll => EventType::FuturePoll,
                 Reason::FutureExit => EventType::FutureExit,
+                Reason::Truncated => EventType::Truncated,
+                Reason::Watch => EventType::Watch,
+                Reason::Catch => EventType::Catch,
                 Reason::Panic => {
                     is_error = true;
                     EventType::Panic
@@ -116,7 +153,8 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
 
             Event {
                 id: NotSet,
-                breakpoint_id: Set(breakpoint_id),
+                run_id: NotSet,
+                breakpoint_id: Set(breakpoint_id as i64),
                 thread_id: Set(thread_id),
                 frame_id: Set(frame_id),
                 parent_frame_id: NotSet,
@@ -127,6 +165,10 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
                 data: Set(data),
                 pretty: Set(pretty),
                 is_error: Set(is_error),
+                clock: Set(None),
+                cpu_time: Set(None),
+                inclusive_time: Set(None),
+                exclusive_time: Set(None),
             }
         }
         SrcEvent::FunctionCall {
@@ -136,6 +178,8 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
             stack_pointer,
             function_name,
             arguments,
+            clock,
+            cpu_time,
         } => {
             let thread_id = thread_id as i64;
             let frame_id = frame_id as i64;
@@ -155,7 +199,8 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
 
             Event {
                 id: NotSet,
-                breakpoint_id: Set(breakpoint_id),
+                run_id: NotSet,
+                breakpoint_id: Set(breakpoint_id as i64),
                 thread_id: Set(thread_id),
                 frame_id: Set(frame_id),
                 parent_frame_id: NotSet,
@@ -166,6 +211,10 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
                 data: Set(data),
                 pretty: Set(pretty),
                 is_error: Set(is_error),
+                clock: Set(nonzero(clock)),
+                cpu_time: Set(nonzero(cpu_time)),
+                inclusive_time: Set(None),
+                exclusive_time: Set(None),
             }
         }
         SrcEvent::FunctionReturn {
@@ -174,6 +223,8 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
             frame_id,
             function_name,
             return_value,
+            clock,
+            cpu_time,
         } => {
             let thread_id = thread_id as i64;
             let frame_id = frame_id as i64;
@@ -183,7 +234,8 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
 
             Event {
                 id: NotSet,
-                breakpoint_id: Set(breakpoint_id),
+                run_id: NotSet,
+                breakpoint_id: Set(breakpoint_id as i64),
                 thread_id: Set(thread_id),
                 frame_id: Set(frame_id),
                 parent_frame_id: NotSet,
@@ -194,8 +246,98 @@ pub fn event(timestamp: Timestamp, event: SrcEvent) -> Event {
                 data: Set(data),
                 pretty: Set(pretty),
                 is_error: Set(is_error),
+                clock: Set(nonzero(clock)),
+                cpu_time: Set(nonzero(cpu_time)),
+                inclusive_time: Set(None),
+                exclusive_time: Set(None),
             }
         }
+        SrcEvent::FunctionUnwind {
+            breakpoint_id,
+            thread_id,
+            frame_id,
+            function_name,
+            clock,
+            cpu_time,
+        } => Event {
+            id: NotSet,
+            run_id: NotSet,
+            breakpoint_id: Set(breakpoint_id as i64),
+            thread_id: Set(thread_id as i64),
+            frame_id: Set(frame_id as i64),
+            parent_frame_id: NotSet,
+            stack_pointer: Set(None),
+            function_name: Set(Some(function_name)),
+            event_type: Set(EventType::FunctionUnwind),
+            timestamp: Set(timestamp),
+            // There is no return value
+            data: Set("null".to_owned()),
+            pretty: Set("unwound".to_owned()),
+            is_error: Set(true),
+            clock: Set(nonzero(clock)),
+            cpu_time: Set(nonzero(cpu_time)),
+            inclusive_time: Set(None),
+            exclusive_time: Set(None),
+        },
+    }
+}
+
+/// A frame which has neither returned nor unwound, e.g. because the debugger crashed or the
+/// program was killed. `clock` is when the frame is known to have ended; 0 if unknown.
+pub fn incomplete_frame(
+    timestamp: Timestamp,
+    breakpoint_id: u32,
+    thread_id: u64,
+    frame_id: u64,
+    function_name: String,
+    clock: u64,
+) -> Event {
+    Event {
+        id: NotSet,
+        run_id: NotSet,
+        breakpoint_id: Set(breakpoint_id as i64),
+        thread_id: Set(thread_id as i64),
+        frame_id: Set(frame_id as i64),
+        parent_frame_id: NotSet,
+        stack_pointer: Set(None),
+        function_name: Set(Some(function_name)),
+        event_type: Set(EventType::Incomplete),
+        timestamp: Set(timestamp),
+        data: Set("null".to_owned()),
+        pretty: Set("incomplete".to_owned()),
+        is_error: Set(false),
+        clock: Set(nonzero(clock)),
+        cpu_time: Set(None),
+        inclusive_time: Set(None),
+        exclusive_time: Set(None),
+    }
+}
+
+pub fn warning(
+    timestamp: Timestamp,
+    kind: &str,
+    stream: &str,
+    thread_frame: Option<(u64, u64)>,
+    message: String,
+) -> Warning {
+    Warning {
+        id: NotSet,
+        run_id: NotSet,
+        kind: Set(kind.to_owned()),
+        stream: Set(stream.to_owned()),
+        thread_id: Set(thread_frame.map(|(thread_id, _)| thread_id as i64)),
+        frame_id: Set(thread_frame.map(|(_, frame_id)| frame_id as i64)),
+        timestamp: Set(timestamp),
+        message: Set(message),
+    }
+}
+
+/// 0 means not recorded
+fn nonzero(v: u64) -> Option<i64> {
+    if v > 0 {
+        Some(v as i64)
+    } else {
+        None
     }
 }
 
@@ -220,6 +362,7 @@ pub fn type_info<F: FnMut(TypeInfo)>(event: &SrcEvent, mut push: F) {
                 push(ty);
             }
         }
+        SrcEvent::FunctionUnwind { .. } => (),
     }
 }
 
@@ -229,8 +372,9 @@ fn type_info_of(value: &RValue) -> Option<TypeInfo> {
         return None;
     }
     Some(TypeInfo {
-        type_name,
-        attributes: match value {
+        run_id: NotSet,
+        type_name: Set(type_name),
+        attributes: Set(match value {
             RValue::Struct { fields, .. } => {
                 let mut s = "[".to_string();
                 for (i, name) in fields.keys().enumerate() {
@@ -241,25 +385,205 @@ fn type_info_of(value: &RValue) -> Option<TypeInfo> {
             }
             RValue::Union { typeinfo, .. } => Some(json_stringify(&typeinfo.variants)),
             _ => None,
-        },
+        }),
     })
 }
 
+/// Walk the locals, arguments or return value of an event into one row per node; the `event_id`
+/// is left for the indexer to set
+pub fn values<F: FnMut(Value)>(event: &SrcEvent, mut push: F) {
+    match event {
+        SrcEvent::Breakpoint { locals, .. } => {
+            for (i, (_, v)) in locals.iter().enumerate() {
+                walk_value(format!("locals.{i}"), v, &mut push);
+            }
+        }
+        SrcEvent::FunctionCall { arguments, .. } => {
+            for (i, (_, v)) in arguments.iter().enumerate() {
+                walk_value(format!("args.{i}"), v, &mut push);
+            }
+        }
+        SrcEvent::FunctionReturn { return_value, .. } => {
+            walk_value("return".to_owned(), return_value, &mut push)
+        }
+        SrcEvent::FunctionUnwind { .. } => (),
+    }
+}
+
+fn walk_value<F: FnMut(Value)>(path: String, value: &RValue, push: &mut F) {
+    let (text, number) = match value {
+        // References are followed as if the value is inline
+        RValue::Ref { value, .. }
+        | RValue::DynRef { value, .. }
+        | RValue::RefCounted { value, .. }
+        | RValue::DynRefCounted { value, .. } => return walk_value(path, value, push),
+        RValue::Prim(v) => {
+            let (text, number) = primitive(v);
+            (Some(text), number)
+        }
+        RValue::String { value, .. } => (
+            Some(value.chars().take(VALUE_MAX_CHARS).collect()),
+            Some(value.chars().count() as f64),
+        ),
+        RValue::Bytes { value, .. } => (None, Some(value.len() as f64)),
+        RValue::Array { data, .. } => (None, Some(data.len() as f64)),
+        RValue::Enum { variant, .. }
+        | RValue::Union { variant, .. }
+        | RValue::Option { variant, .. }
+        | RValue::Result { variant, .. } => (Some(variant.clone()), None),
+        RValue::Unit
+        | RValue::UnresolvedRef { .. }
+        | RValue::Struct { .. }
+        | RValue::Tuple { .. }
+        | RValue::Opaque => (None, None),
+    };
+    push(Value {
+        id: NotSet,
+        run_id: NotSet,
+        event_id: NotSet,
+        path: Set(path.clone()),
+        type_name: Set(value.typename()),
+        value: Set(text),
+        number: Set(number),
+    });
+    match value {
+        RValue::Struct { fields, .. } => {
+            for (name, v) in fields {
+                walk_value(format!("{path}.{name}"), v, push);
+            }
+        }
+        RValue::Tuple { items, .. } => {
+            for (i, v) in items.iter().enumerate() {
+                walk_value(format!("{path}.{i}"), v, push);
+            }
+        }
+        RValue::Array { data, .. } => {
+            for (i, v) in data.iter().enumerate() {
+                walk_value(format!("{path}[{i}]"), v, push);
+            }
+        }
+        // The payload is under the variant, e.g. `return.Err`
+        RValue::Union {
+            variant, fields, ..
+        } => {
+            for (name, v) in fields {
+                walk_value(format!("{path}.{variant}.{name}"), v, push);
+            }
+        }
+        RValue::Option {
+            variant,
+            value: Some(v),
+            ..
+        } => walk_value(format!("{path}.{variant}"), v, push),
+        RValue::Result { variant, value, .. } => {
+            walk_value(format!("{path}.{variant}"), value, push)
+        }
+        _ => (),
+    }
+}
+
+/// The text and numeric value of a primitive; the text has no type suffix
+fn primitive(v: &PValue) -> (String, Option<f64>) {
+    match *v {
+        PValue::bool(v) => (v.to_string(), None),
+        PValue::char(v) => (v.to_string(), None),
+        PValue::u8(v) => (v.to_string(), Some(v as f64)),
+        PValue::i8(v) => (v.to_string(), Some(v as f64)),
+        PValue::u16(v) => (v.to_string(), Some(v as f64)),
+        PValue::i16(v) => (v.to_string(), Some(v as f64)),
+        PValue::u32(v) => (v.to_string(), Some(v as f64)),
+        PValue::i32(v) => (v.to_string(), Some(v as f64)),
+        PValue::u64(v) | PValue::usize(v) => (v.to_string(), Some(v as f64)),
+        PValue::i64(v) | PValue::isize(v) => (v.to_string(), Some(v as f64)),
+        PValue::u128(v) => (v.to_string(), Some(v as f64)),
+        PValue::i128(v) => (v.to_string(), Some(v as f64)),
+        PValue::f32(v) => (v.to_string(), Some(v as f64)),
+        PValue::f64(v) => (v.to_string(), Some(v)),
+    }
+}
+
 pub fn allocation(bp: SrcAllocation) -> Allocation {
     let SrcAllocation {
         action,
         address,
+        size,
+        align,
+        old_address,
+        thread_id,
+        frame_id,
         type_name,
     } = bp;
 
     Allocation {
         id: NotSet,
+        run_id: NotSet,
         action: Set(action.to_string()),
         address: Set(address as i64),
+        size: Set(size as i64),
+        align: Set(align as i64),
+        old_address: Set(old_address.map(|addr| addr as i64)),
+        thread_id: Set(thread_id as i64),
+        frame_id: Set(if frame_id == 0 {
+            None
+        } else {
+            Some(frame_id as i64)
+        }),
         type_name: Set(type_name),
     }
 }
 
+pub fn output(timestamp: Timestamp, output: SrcOutput) -> Output {
+    let SrcOutput {
+        channel,
+        thread_id,
+        frame_id,
+        clock,
+        content,
+    } = output;
+
+    Output {
+        id: NotSet,
+        run_id: NotSet,
+        channel: Set(channel.to_string()),
+        thread_id: Set(thread_id as i64),
+        frame_id: Set(if frame_id == 0 {
+            None
+        } else {
+            Some(frame_id as i64)
+        }),
+        timestamp: Set(timestamp),
+        clock: Set(clock as i64),
+        content: Set(content),
+    }
+}
+
+pub fn panic(timestamp: Timestamp, panic: SrcPanic) -> Panic {
+    let SrcPanic {
+        thread_id,
+        frame_id,
+        message,
+        location,
+        backtrace,
+    } = panic;
+
+    Panic {
+        id: NotSet,
+        run_id: NotSet,
+        thread_id: Set(thread_id as i64),
+        frame_id: Set(if frame_id == 0 {
+            None
+        } else {
+            Some(frame_id as i64)
+        }),
+        timestamp: Set(timestamp),
+        message: Set(message),
+        file: Set(location.as_ref().map(|location| location.file.clone())),
+        line: Set(location.as_ref().map(|location| location.line as i64)),
+        column: Set(location.map(|location| location.column as i64)),
+        backtrace: Set(json_stringify(&backtrace)),
+    }
+}
+
 fn value_is_error(value: &RValue) -> bool {
     if value.is_result() {
         value.result_variant().is_err()
@@ -274,3 +598,233 @@ where
 {
     serde_json::to_string(v).expect("Fail to serialize into JSON string")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use firedbg_rust_debugger::{ArrayType, RefAddr, RefType, StringType, UnionType};
+
+    /// (path, type_name, value, number) of each node
+    fn walk(event: &SrcEvent) -> Vec<(String, String, Option<String>, Option<f64>)> {
+        let mut rows = Vec::new();
+        values(event, |v| {
+            rows.push((
+                v.path.as_ref().clone(),
+                v.type_name.as_ref().clone(),
+                v.value.as_ref().clone(),
+                *v.number.as_ref(),
+            ))
+        });
+        rows
+    }
+
+    fn row(
+        path: &str,
+        type_name: &str,
+        value: Option<&str>,
+        number: Option<f64>,
+    ) -> (String, String, Option<String>, Option<f64>) {
+        (
+            path.to_owned(),
+            type_name.to_owned(),
+            value.map(|v| v.to_owned()),
+            number,
+        )
+    }
+
+    fn string(value: &str) -> RValue {
+        RValue::String {
+            typename: StringType::String,
+            value: value.to_owned(),
+        }
+    }
+
+    fn function_call(arguments: Vec<RValue>) -> SrcEvent {
+        SrcEvent::FunctionCall {
+            breakpoint_id: 1,
+            thread_id: 1,
+            frame_id: 1,
+            stack_pointer: 0,
+            function_name: "f".to_owned(),
+            arguments: arguments
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| (format!("a{i}"), v))
+                .collect(),
+            clock: 0,
+            cpu_time: 0,
+        }
+    }
+
+    fn function_return(return_value: RValue) -> SrcEvent {
+        SrcEvent::FunctionReturn {
+            breakpoint_id: 1,
+            thread_id: 1,
+            frame_id: 1,
+            function_name: "f".to_owned(),
+            return_value,
+            clock: 0,
+            cpu_time: 0,
+        }
+    }
+
+    #[test]
+    fn test_values_nested() {
+        let item = |name: &str| RValue::Struct {
+            typename: "Item".to_owned(),
+            fields: [("name".to_owned(), string(name))].into_iter().collect(),
+        };
+        let order = RValue::Struct {
+            typename: "Order".to_owned(),
+            fields: [
+                ("id".to_owned(), RValue::Prim(PValue::u64(7))),
+                (
+                    "items".to_owned(),
+                    RValue::Array {
+                        typename: ArrayType::Vec,
+                        data: vec![item("a"), item("b"), item("c"), item("d")],
+                    },
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        // The reference is followed without adding to the path
+        let rows = walk(&function_call(vec![
+            RValue::Ref {
+                typename: RefType::Ref,
+                addr: RefAddr::Redacted,
+                value: Box::new(order),
+            },
+            RValue::Tuple {
+                typename: "(bool, char)".to_owned(),
+                items: vec![
+                    RValue::Prim(PValue::bool(true)),
+                    RValue::Prim(PValue::char('x')),
+                ],
+            },
+        ]));
+        assert_eq!(
+            rows,
+            [
+                row("args.0", "Order", None, None),
+                row("args.0.id", "u64", Some("7"), Some(7.0)),
+                row("args.0.items", "Vec<Item>", None, Some(4.0)),
+                row("args.0.items[0]", "Item", None, None),
+                row("args.0.items[0].name", "String", Some("a"), Some(1.0)),
+                row("args.0.items[1]", "Item", None, None),
+                row("args.0.items[1].name", "String", Some("b"), Some(1.0)),
+                row("args.0.items[2]", "Item", None, None),
+                row("args.0.items[2].name", "String", Some("c"), Some(1.0)),
+                row("args.0.items[3]", "Item", None, None),
+                row("args.0.items[3].name", "String", Some("d"), Some(1.0)),
+                row("args.1", "(bool, char)", None, None),
+                row("args.1.0", "bool", Some("true"), None),
+                row("args.1.1", "char", Some("x"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_enum() {
+        let rows = walk(&function_return(RValue::Result {
+            typename: "Result<u8, Error>".to_owned(),
+            variant: "Err".to_owned(),
+            value: Box::new(RValue::Enum {
+                typename: "Error".to_owned(),
+                variant: "Timeout".to_owned(),
+            }),
+        }));
+        assert_eq!(
+            rows,
+            [
+                row("return", "Result<u8, Error>", Some("Err"), None),
+                row("return.Err", "Error", Some("Timeout"), None),
+            ]
+        );
+
+        let rows = walk(&SrcEvent::Breakpoint {
+            breakpoint_id: 1,
+            thread_id: 1,
+            frame_id: 1,
+            reason: Reason::Breakpoint,
+            locals: vec![
+                (
+                    "cmd".to_owned(),
+                    RValue::Union {
+                        typeinfo: UnionType {
+                            name: "Command".to_owned(),
+                            variants: vec!["Stop".to_owned(), "Move".to_owned()],
+                        },
+                        variant: "Move".to_owned(),
+                        fields: [
+                            ("x".to_owned(), RValue::Prim(PValue::i32(-1))),
+                            ("y".to_owned(), RValue::Prim(PValue::f64(2.5))),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    },
+                ),
+                (
+                    "some".to_owned(),
+                    RValue::Option {
+                        typename: "Option<u8>".to_owned(),
+                        variant: "Some".to_owned(),
+                        value: Some(Box::new(RValue::Prim(PValue::u8(3)))),
+                    },
+                ),
+                (
+                    "none".to_owned(),
+                    RValue::Option {
+                        typename: "Option<u8>".to_owned(),
+                        variant: "None".to_owned(),
+                        value: None,
+                    },
+                ),
+            ],
+        });
+        assert_eq!(
+            rows,
+            [
+                row("locals.0", "Command", Some("Move"), None),
+                row("locals.0.Move.x", "i32", Some("-1"), Some(-1.0)),
+                row("locals.0.Move.y", "f64", Some("2.5"), Some(2.5)),
+                row("locals.1", "Option<u8>", Some("Some"), None),
+                row("locals.1.Some", "u8", Some("3"), Some(3.0)),
+                row("locals.2", "Option<u8>", Some("None"), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_string() {
+        // The length is in chars, not bytes
+        let long = "é".repeat(VALUE_MAX_CHARS + 44);
+        let rows = walk(&function_call(vec![string(&long), string("")]));
+        assert_eq!(
+            rows,
+            [
+                row(
+                    "args.0",
+                    "String",
+                    Some(&"é".repeat(VALUE_MAX_CHARS)),
+                    Some(300.0)
+                ),
+                row("args.1", "String", Some(""), Some(0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_unwind() {
+        let rows = walk(&SrcEvent::FunctionUnwind {
+            breakpoint_id: 1,
+            thread_id: 1,
+            frame_id: 1,
+            function_name: "f".to_owned(),
+            clock: 0,
+            cpu_time: 0,
+        });
+        assert!(rows.is_empty());
+    }
+}
@@ -5,23 +5,25 @@ pub fn deser<T: serde::de::DeserializeOwned>(m: &SharedMessage) -> T {
     try_deser(m).expect("Deserialization failed")
 }
 
-fn try_deser<T: serde::de::DeserializeOwned>(m: &SharedMessage) -> Result<T, String> {
+pub fn try_deser<T: serde::de::DeserializeOwned>(m: &SharedMessage) -> Result<T, String> {
     m.message().deserialize_json().map_err(|e| {
         format!(
             "Failed to deserialize message `{}`: {}",
-            m.message()
-                .as_str()
-                .expect("Failed to convert message to &str"),
+            m.message().as_str().unwrap_or("<binary>"),
             e
         )
     })
 }
 
 pub fn deser_info(m: &SharedMessage) -> InfoMessage {
+    try_deser_info(m).expect("Deserialization failed")
+}
+
+pub fn try_deser_info(m: &SharedMessage) -> Result<InfoMessage, String> {
     if let Ok(info) = try_deser::<InfoMessage>(m) {
-        info
+        Ok(info)
     } else {
-        let info = deser::<DebuggerInfo>(m);
-        InfoMessage::Debugger(info)
+        let info = try_deser::<DebuggerInfo>(m)?;
+        Ok(InfoMessage::Debugger(info))
     }
 }
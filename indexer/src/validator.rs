@@ -10,7 +10,7 @@ use crate::{
 };
 use firedbg_rust_debugger::{
     Breakpoint, Event, EventStream, InfoMessage, SourceFile, ALLOCATION_STREAM, BREAKPOINT_STREAM,
-    EVENT_STREAM, FILE_STREAM, INFO_STREAM,
+    EVENT_STREAM, FILE_STREAM, INFO_STREAM, OUTPUT_STREAM, PANIC_STREAM,
 };
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -57,17 +57,25 @@ impl Processor for Validator {
                     event.redacted();
                     self.data.events.push(event);
                 }
-                ALLOCATION_STREAM => {}
+                ALLOCATION_STREAM | OUTPUT_STREAM | PANIC_STREAM => {}
                 _ => anyhow::bail!("Unexpected stream key {}", message.stream_key()),
             }
         }
         Ok(())
     }
 
+    async fn idle(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     async fn end(&mut self) -> Result<()> {
         Ok(())
     }
 
+    async fn abort(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     fn finish(self) -> Result<()> {
         // println!("{}", serde_json::to_string_pretty(&self.data.events)?); std::process::exit(1);
 
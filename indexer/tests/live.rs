@@ -0,0 +1,50 @@
+mod util;
+use util::*;
+
+use anyhow::Result;
+use firedbg_stream_indexer::{database::Database, entity::event};
+use pretty_assertions::assert_eq;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use std::{
+    process::Command,
+    time::{Duration, Instant},
+};
+
+#[tokio::test]
+async fn main() -> Result<()> {
+    let recording = Recording::new("live").await?;
+    recording.event(function_call(1, 1, 1, "main", &[]))?;
+    recording.event(function_call(2, 1, 2, "hello", &[("i", 5)]))?;
+
+    // Follow the recording while it is still being written
+    let output = format!("{}.sqlite", recording.file_id.path());
+    let mut indexer = Command::new(env!("CARGO_BIN_EXE_firedbg-indexer"))
+        .arg("--input")
+        .arg(recording.file_id.path())
+        .arg("--output")
+        .arg(&output)
+        .spawn()?;
+
+    // The recording pauses; what has been indexed so far is queryable without waiting for more
+    let start = Instant::now();
+    let mut count = 0;
+    while count != 2 && start.elapsed() < Duration::from_secs(10) {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        if let Ok(mut db) = Database::open(output.clone()).await {
+            count = event::Entity::find().count(db.db()).await.unwrap_or(0);
+            db.close().await?;
+        }
+    }
+    assert_eq!(count, 2);
+
+    recording.event(function_return(2, 1, 2, "hello", 6))?;
+    recording.event(function_return(1, 1, 1, "main", 0))?;
+    recording.end().await?;
+    let status = indexer.wait()?;
+    assert!(status.success(), "firedbg-indexer exited with {status}");
+
+    let db = Database::open(output).await?;
+    assert_eq!(event::Entity::find().count(db.db()).await?, 4);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+mod util;
+use util::*;
+
+use anyhow::Result;
+use firedbg_rust_debugger::Bytes;
+use firedbg_stream_indexer::{
+    database::Database,
+    entity::{event, event::EventType, run, warning},
+};
+use pretty_assertions::assert_eq;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+#[tokio::test]
+async fn main() -> Result<()> {
+    let recording = Recording::new("recovery").await?;
+    recording.event(function_call(1, 1, 1, "main", &[]))?;
+    recording.event(function_call(2, 1, 2, "hello", &[("i", 5)]))?;
+    recording.event(function_call(2, 1, 3, "hello", &[("i", 6)]))?;
+    // Frame 3 never returns
+    recording.event(function_return(2, 1, 2, "hello", 6))?;
+    // Frame 9 was never called
+    recording.event(function_return(2, 1, 9, "hello", 7))?;
+    // The debugger was killed in the middle of writing an event
+    let event = function_call(2, 1, 4, "hello", &[("i", 7)]).into_bytes();
+    recording.event(Bytes::from(event[..event.len() - 6].to_vec()))?;
+    // `main` never returns
+    let input = recording.end().await?;
+
+    let output = format!("{}.sqlite", input.path());
+    index(&input, &["--output", &output]);
+
+    let db = Database::open(output).await?;
+    let runs = run::Entity::find().all(db.db()).await?;
+    assert_eq!(runs.len(), 1);
+    let run_id = runs[0].id;
+
+    let events = event::Entity::find()
+        .filter(event::Column::RunId.eq(run_id))
+        .order_by_asc(event::Column::Id)
+        .all(db.db())
+        .await?;
+    assert_eq!(
+        events
+            .iter()
+            .map(|e| (e.id, e.event_type.clone(), e.frame_id))
+            .collect::<Vec<_>>(),
+        [
+            (1, EventType::FunctionCall, 1),
+            (2, EventType::FunctionCall, 2),
+            (3, EventType::FunctionCall, 3),
+            (4, EventType::Incomplete, 3),
+            (5, EventType::FunctionReturn, 2),
+            (6, EventType::FunctionReturn, 9),
+            (7, EventType::Incomplete, 1),
+        ]
+    );
+    assert_eq!(events[2].parent_frame_id, Some(2));
+
+    let warnings = warning::Entity::find()
+        .filter(warning::Column::RunId.eq(run_id))
+        .order_by_asc(warning::Column::Id)
+        .all(db.db())
+        .await?;
+    assert_eq!(
+        warnings
+            .iter()
+            .map(|w| (w.kind.as_str(), w.frame_id))
+            .collect::<Vec<_>>(),
+        [
+            (warning::INCOMPLETE_FRAME, Some(3)),
+            (warning::UNMATCHED_RETURN, Some(9)),
+            (warning::UNDECODABLE, None),
+            (warning::INCOMPLETE_FRAME, Some(1)),
+        ]
+    );
+    assert!(warnings[2].message.starts_with("Fail to decode event"));
+
+    Ok(())
+}
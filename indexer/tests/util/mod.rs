@@ -0,0 +1,250 @@
+#![allow(dead_code)]
+
+use firedbg_rust_debugger::{
+    AllocAction, Allocation, Breakpoint, BreakpointType, Bytes, DebuggerInfo, FireDbgForRust,
+    InfoMessage, LineColumn, Output, OutputChannel, Panic, SourceFile, VariableCapture,
+    ALLOCATION_STREAM, BREAKPOINT_STREAM, EVENT_STREAM, FILE_STREAM, INFO_STREAM, OUTPUT_STREAM,
+    PANIC_STREAM,
+};
+use sea_streamer::{
+    file::FileId, Error as SeaStreamerErr, Producer, SeaConnectOptions, SeaProducer, SeaStreamer,
+    StreamKey, Streamer, Timestamp,
+};
+use std::{
+    fs::OpenOptions,
+    process::{Command, ExitStatus},
+    time::SystemTime,
+};
+
+/// A recording written by hand, in place of the debugger
+pub struct Recording {
+    pub file_id: FileId,
+    producer: SeaProducer,
+}
+
+impl Recording {
+    /// Create a recording with the debugger info, a source file and two breakpoints
+    pub async fn new(testcase: &str) -> Result<Self, SeaStreamerErr> {
+        let file_id = temp_file(testcase).unwrap();
+        println!("{file_id:?}");
+        let mut options = SeaConnectOptions::default();
+        options.set_file_connect_options(|options| {
+            options.set_end_with_eos(true);
+        });
+        let streamer = SeaStreamer::connect(file_id.to_streamer_uri()?, options).await?;
+        let producer = streamer.create_generic_producer(Default::default()).await?;
+        let recording = Self { file_id, producer };
+
+        recording.send_json(
+            INFO_STREAM,
+            &InfoMessage::Debugger(DebuggerInfo {
+                debugger: FireDbgForRust,
+                version: env!("CARGO_PKG_VERSION").to_owned(),
+                workspace_root: "".to_owned(),
+                package_name: testcase.to_owned(),
+                target: testcase.to_owned(),
+                arguments: vec![],
+                env: Default::default(),
+                env_remove: vec![],
+                cwd: None,
+            }),
+        )?;
+        recording.send_json(
+            FILE_STREAM,
+            &SourceFile {
+                id: 1,
+                path: format!("src/{testcase}.rs"),
+                crate_name: testcase.to_owned(),
+                modified: SystemTime::UNIX_EPOCH,
+            },
+        )?;
+        recording.breakpoint(1, "main")?;
+        recording.breakpoint(2, "hello")?;
+
+        Ok(recording)
+    }
+
+    pub fn breakpoint(&self, id: u32, fn_name: &str) -> Result<(), SeaStreamerErr> {
+        self.send_json(
+            BREAKPOINT_STREAM,
+            &Breakpoint {
+                id,
+                file_id: 1,
+                loc: LineColumn {
+                    line: id * 10,
+                    column: Some(1),
+                },
+                loc_end: None,
+                breakpoint_type: BreakpointType::FunctionCall {
+                    fn_name: fn_name.to_owned(),
+                },
+                capture: VariableCapture::Arguments,
+            },
+        )
+    }
+
+    pub fn send_json<T: serde::Serialize>(
+        &self,
+        stream: &str,
+        value: &T,
+    ) -> Result<(), SeaStreamerErr> {
+        self.producer.send_to(
+            &StreamKey::new(stream)?,
+            serde_json::to_string(value).unwrap().as_str(),
+        )?;
+        Ok(())
+    }
+
+    pub fn event(&self, event: Bytes) -> Result<(), SeaStreamerErr> {
+        self.producer
+            .send_to(&StreamKey::new(EVENT_STREAM)?, event)?;
+        Ok(())
+    }
+
+    pub fn allocation(&self, thread_id: u64, frame_id: u64) -> Result<(), SeaStreamerErr> {
+        self.send_json(
+            ALLOCATION_STREAM,
+            &Allocation {
+                action: AllocAction::Alloc,
+                address: 0x1000,
+                size: 16,
+                align: 8,
+                old_address: None,
+                thread_id,
+                frame_id,
+                type_name: "".to_owned(),
+            },
+        )
+    }
+
+    pub fn output(
+        &self,
+        thread_id: u64,
+        frame_id: u64,
+        content: &str,
+    ) -> Result<(), SeaStreamerErr> {
+        self.send_json(
+            OUTPUT_STREAM,
+            &Output {
+                channel: OutputChannel::Stdout,
+                thread_id,
+                frame_id,
+                clock: 0,
+                content: content.to_owned(),
+            },
+        )
+    }
+
+    pub fn panic(
+        &self,
+        thread_id: u64,
+        frame_id: u64,
+        message: &str,
+    ) -> Result<(), SeaStreamerErr> {
+        self.send_json(
+            PANIC_STREAM,
+            &Panic {
+                thread_id,
+                frame_id,
+                message: Some(message.to_owned()),
+                location: None,
+                backtrace: vec![],
+            },
+        )
+    }
+
+    pub async fn end(self) -> Result<FileId, SeaStreamerErr> {
+        self.producer.end().await?;
+        Ok(self.file_id)
+    }
+}
+
+/// Same encoding as `EventStream::function_call`, with `u64` arguments
+pub fn function_call(
+    breakpoint_id: u32,
+    thread_id: u64,
+    frame_id: u64,
+    function_name: &str,
+    arguments: &[(&str, u64)],
+) -> Bytes {
+    let mut bytes = Bytes::new();
+    bytes.push_byte(b'F');
+    for i in [breakpoint_id as u64, thread_id, frame_id, 0, 0, 0] {
+        integer(&mut bytes, i);
+    }
+    identifier(&mut bytes, function_name);
+    for (name, value) in arguments {
+        u64_value(&mut bytes, name, *value);
+    }
+    bytes
+}
+
+/// Same encoding as `EventStream::function_return`, with a `u64` return value
+pub fn function_return(
+    breakpoint_id: u32,
+    thread_id: u64,
+    frame_id: u64,
+    function_name: &str,
+    return_value: u64,
+) -> Bytes {
+    let mut bytes = Bytes::new();
+    bytes.push_byte(b'R');
+    for i in [breakpoint_id as u64, thread_id, frame_id, 0, 0] {
+        integer(&mut bytes, i);
+    }
+    identifier(&mut bytes, function_name);
+    u64_value(&mut bytes, "return_value", return_value);
+    bytes
+}
+
+fn integer(bytes: &mut Bytes, i: u64) {
+    bytes.push_string(i.to_string());
+    bytes.push_byte(b' ');
+}
+
+fn identifier(bytes: &mut Bytes, s: &str) {
+    bytes.push_str("\" ");
+    bytes.push_str(s);
+    bytes.push_str("\"");
+}
+
+fn u64_value(bytes: &mut Bytes, name: &str, value: u64) {
+    identifier(bytes, name);
+    bytes.push_str("name ");
+    identifier(bytes, "u64");
+    bytes.push_str("# ");
+    bytes.push_slice(&8u32.to_ne_bytes());
+    bytes.push_slice(&value.to_ne_bytes());
+    bytes.push_str(" prim ");
+}
+
+/// Run `firedbg-indexer` on the recording
+pub fn index(file_id: &FileId, args: &[&str]) {
+    let status = try_index(file_id, args);
+    assert!(status.success(), "firedbg-indexer exited with {status}");
+}
+
+pub fn try_index(file_id: &FileId, args: &[&str]) -> ExitStatus {
+    Command::new(env!("CARGO_BIN_EXE_firedbg-indexer"))
+        .arg("--input")
+        .arg(file_id.path())
+        .args(args)
+        .status()
+        .unwrap()
+}
+
+pub fn temp_file(name: &str) -> Result<FileId, std::io::Error> {
+    let name = format!("{}-{}", name, millis_of(&Timestamp::now_utc()));
+    let path = format!("/tmp/{name}.firedbg.ss");
+    let _file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+
+    Ok(FileId::new(path))
+}
+
+fn millis_of(ts: &Timestamp) -> i64 {
+    (ts.unix_timestamp_nanos() / 1_000_000) as i64
+}
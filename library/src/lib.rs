@@ -27,6 +27,32 @@
 //! ```ignore
 //! fn __firedbg_trace__<T>(name: &'static str, v: T) -> T { v }
 //! ```
+//!
+//! ### `fire::watch!`
+//!
+//! This macro asks FireDBG to install a hardware watchpoint on the memory behind a reference.
+//! Every subsequent write to that memory is recorded as a `Watch` event, carrying the old value,
+//! the new value and the function that performed the write.
+//!
+//! ```
+//! use firedbg_lib::fire;
+//!
+//! fn some_fn() {
+//!     let mut counter = 0;
+//!     fire::watch!(&counter);
+//!     for i in 0..3 {
+//!         counter += i;
+//!     }
+//! }
+//! ```
+//!
+//! A watchpoint on a stack variable is removed when the function owning it returns.
+//! The number of hardware watchpoints is limited (typically 4 on x86_64 and aarch64);
+//! watches beyond the limit are ignored. In release mode, the macro only evaluates the expression.
+//!
+//! ```ignore
+//! fn __firedbg_watch__<T: ?Sized>(name: &'static str, v: &T) {}
+//! ```
 pub mod fire {
     #[macro_export]
     #[cfg(debug_assertions)]
@@ -50,7 +76,30 @@ pub mod fire {
         }};
     }
 
+    #[macro_export]
+    #[cfg(debug_assertions)]
+    macro_rules! watch {
+        ($t:expr, $v:expr) => {
+            firedbg_lib::__firedbg_watch__($t, $v);
+        };
+        ($v:expr) => {
+            firedbg_lib::__firedbg_watch__(std::stringify!($v), $v);
+        };
+    }
+
+    #[macro_export]
+    #[cfg(not(debug_assertions))]
+    macro_rules! watch {
+        ($t:expr, $v:expr) => {{
+            let _ = $v;
+        }};
+        ($v:expr) => {{
+            let _ = $v;
+        }};
+    }
+
     pub use dbg;
+    pub use watch;
 }
 
 #[cfg(debug_assertions)]
@@ -58,3 +107,8 @@ pub mod fire {
 pub fn __firedbg_trace__<T>(name: &'static str, v: T) -> T {
     v
 }
+
+#[cfg(debug_assertions)]
+#[allow(unused_variables)]
+#[inline(never)]
+pub fn __firedbg_watch__<T: ?Sized>(name: &'static str, v: &T) {}
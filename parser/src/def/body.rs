@@ -86,6 +86,16 @@ pub enum ExpressionType {
     Other(String),
 }
 
+impl StmtOrExpr {
+    /// Location of the end of this statement or expression
+    pub fn loc(&self) -> &LineColumn {
+        match self {
+            Self::Stmt(stmt) => &stmt.loc,
+            Self::Expr(expr) => &expr.loc,
+        }
+    }
+}
+
 impl From<Statement> for StmtOrExpr {
     fn from(stmt: Statement) -> Self {
         Self::Stmt(stmt)
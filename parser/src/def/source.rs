@@ -1,4 +1,4 @@
-use crate::FunctionDef;
+use crate::{FunctionDef, StmtOrExpr};
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 
@@ -6,6 +6,9 @@ use std::time::SystemTime;
 pub struct File {
     pub path: String,
     pub functions: Vec<FunctionDef>,
+    /// Statements and expressions in the function bodies; used by `trace = "statements"`
+    #[serde(default)]
+    pub statements: Vec<StmtOrExpr>,
     pub crate_name: String,
     pub modified: SystemTime,
 }
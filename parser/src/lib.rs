@@ -66,14 +66,7 @@ pub fn parse_file<T>(path: T) -> Result<Vec<FunctionDef>>
 where
     T: AsRef<Path>,
 {
-    let path = path.as_ref();
-    let mut file = std::fs::File::open(path)
-        .with_context(|| format!("Fail to open file: `{}`", path.display()))?;
-    let mut source_code = String::new();
-    file.read_to_string(&mut source_code)
-        .with_context(|| format!("Fail to read file: `{}`", path.display()))?;
-    let res = syn::parse_file(&source_code)
-        .with_context(|| format!("Fail to parse file: `{}`", path.display()))?
+    let res = read_syn_file(path.as_ref())?
         .items
         .into_iter()
         .fold(Vec::new(), |mut acc, item| {
@@ -83,6 +76,25 @@ where
     Ok(res)
 }
 
+/// Parse the statements and expressions in the body of every function, in order of appearance
+pub fn parse_file_statements<T>(path: T) -> Result<Vec<StmtOrExpr>>
+where
+    T: AsRef<Path>,
+{
+    let res = parse_fn_bodies(&read_syn_file(path.as_ref())?.items);
+    Ok(res)
+}
+
+fn read_syn_file(path: &Path) -> Result<syn::File> {
+    let mut file = std::fs::File::open(path)
+        .with_context(|| format!("Fail to open file: `{}`", path.display()))?;
+    let mut source_code = String::new();
+    file.read_to_string(&mut source_code)
+        .with_context(|| format!("Fail to read file: `{}`", path.display()))?;
+    syn::parse_file(&source_code)
+        .with_context(|| format!("Fail to parse file: `{}`", path.display()))
+}
+
 pub fn parse_directory<T>(directory: T) -> Result<Vec<File>>
 where
     T: Into<String>,
@@ -95,9 +107,12 @@ where
         let file_path = path_to_str(&path).into();
         let functions = parse_file(&path)
             .with_context(|| format!("Fail to parse file: `{}`", path.display()))?;
+        let statements = parse_file_statements(&path)
+            .with_context(|| format!("Fail to parse file: `{}`", path.display()))?;
         res.push(File {
             path: file_path,
             functions,
+            statements,
             crate_name: "".into(),
             modified,
         });
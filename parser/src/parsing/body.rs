@@ -27,6 +27,52 @@ pub(crate) fn parse_body(syn_stmts: &[syn::Stmt]) -> Vec<StmtOrExpr> {
     })
 }
 
+// Parse the body of every function, including impl functions, trait default functions,
+// functions in inline modules and nested functions
+pub(crate) fn parse_fn_bodies(items: &[syn::Item]) -> Vec<StmtOrExpr> {
+    items.iter().fold(Vec::new(), |mut acc, item| {
+        match item {
+            syn::Item::Fn(item_fn) => acc.extend(parse_fn_body(&item_fn.block)),
+            syn::Item::Impl(item_impl) => {
+                for impl_item in item_impl.items.iter() {
+                    if let syn::ImplItem::Fn(impl_item_fn) = impl_item {
+                        acc.extend(parse_fn_body(&impl_item_fn.block));
+                    }
+                }
+            }
+            syn::Item::Trait(item_trait) => {
+                for trait_item in item_trait.items.iter() {
+                    if let syn::TraitItem::Fn(syn::TraitItemFn {
+                        default: Some(block),
+                        ..
+                    }) = trait_item
+                    {
+                        acc.extend(parse_fn_body(block));
+                    }
+                }
+            }
+            syn::Item::Mod(item_mod) => {
+                if let Some((_, items)) = &item_mod.content {
+                    acc.extend(parse_fn_bodies(items));
+                }
+            }
+            _ => {}
+        }
+        acc
+    })
+}
+
+// Parse the statements or expressions of a function body, followed by the bodies of its nested functions
+fn parse_fn_body(block: &syn::Block) -> Vec<StmtOrExpr> {
+    let mut body = parse_body(&block.stmts);
+    for stmt in block.stmts.iter() {
+        if let syn::Stmt::Item(item) = stmt {
+            body.extend(parse_fn_bodies(std::slice::from_ref(item)));
+        }
+    }
+    body
+}
+
 // Parse any AST into statement or expression
 pub(crate) trait ParseBody {
     fn parse_body(&self) -> Vec<StmtOrExpr>;
@@ -1,10 +1,9 @@
-#[allow(dead_code)]
 mod body;
-
 mod function;
 mod source;
 mod workspace;
 
+pub(crate) use body::*;
 pub(crate) use function::*;
 pub(crate) use source::*;
 pub(crate) use workspace::*;
@@ -11,3 +11,43 @@ fn parse_stmt() -> anyhow::Result<()> {
     assert_eq!(breakpoints, common::stmt::get_breakpoints());
     Ok(())
 }
+
+#[test]
+fn parse_stmt_body() -> anyhow::Result<()> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/common/stmt.rs");
+    let statements = parse_file_statements(path)?;
+    let statement_types: Vec<_> = statements
+        .iter()
+        .filter_map(|stmt_or_expr| match stmt_or_expr {
+            StmtOrExpr::Stmt(stmt) => Some(&stmt.ty),
+            StmtOrExpr::Expr(_) => None,
+        })
+        .collect();
+    assert!(statement_types.contains(&&StatementType::Assign {
+        binding: Binding::Var("b".into()),
+        assign_op: AssignOp::AddAssign,
+    }));
+    assert!(statement_types.contains(&&StatementType::Constant {
+        binding: Binding::Var("PI_CONST".into()),
+    }));
+    assert!(statement_types.contains(&&StatementType::Assign {
+        binding: Binding::Field {
+            base: "nested_of_6".into(),
+            inter: Some("0.4.3.2.1".into()),
+            member: "0".into(),
+        },
+        assign_op: AssignOp::Assign,
+    }));
+    // Statements of the nested functions
+    assert!(statement_types.contains(&&StatementType::LetAssign {
+        binding: Binding::Var("cal_e".into()),
+        mutable: false,
+    }));
+    assert!(statement_types.contains(&&StatementType::LetAssign {
+        binding: Binding::Var("vec".into()),
+        mutable: true,
+    }));
+    // `let a;` is the first statement
+    assert_eq!(statements[0].loc().line, 2);
+    Ok(())
+}
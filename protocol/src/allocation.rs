@@ -5,11 +5,24 @@ use strum::{Display, EnumString};
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 /// Allocation
 pub struct Allocation {
-    // TODO
-    // thread_id
-    // frame_id
     pub action: AllocAction,
     pub address: u64,
+    /// Size in bytes; 0 if unknown
+    #[serde(default)]
+    pub size: u64,
+    /// Alignment in bytes; 0 if unknown
+    #[serde(default)]
+    pub align: u64,
+    /// The address being reallocated; only set for `Realloc`
+    #[serde(default)]
+    pub old_address: Option<u64>,
+    /// The thread making the allocation
+    #[serde(default)]
+    pub thread_id: u64,
+    /// The innermost recorded frame making the allocation; 0 if none
+    #[serde(default)]
+    pub frame_id: u64,
+    /// Empty if the allocation is not made by `Box` / `Rc` / `Arc`
     pub type_name: String,
 }
 
@@ -18,15 +31,24 @@ pub struct Allocation {
 pub struct AllocationBorrowed<'a> {
     pub action: AllocAction,
     pub address: u64,
+    pub size: u64,
+    pub align: u64,
+    pub old_address: Option<u64>,
+    pub thread_id: u64,
+    pub frame_id: u64,
     pub type_name: &'a str,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
 /// Allocation action
 pub enum AllocAction {
-    /// Allocation: originates from `exchange_malloc`
+    /// Allocation: originates from `exchange_malloc` or `__rust_alloc`
     Alloc,
-    /// Deallocation: originates from `drop_in_place`
+    /// Reallocation: originates from `__rust_realloc`
+    Realloc,
+    /// Deallocation: originates from `__rust_dealloc`
+    Dealloc,
+    /// Drop: originates from `drop_in_place`
     Drop,
 }
 
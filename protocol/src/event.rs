@@ -19,6 +19,13 @@ pub enum Event {
         stack_pointer: u64,
         function_name: String,
         arguments: Vec<(String, RValue)>,
+        /// Nanoseconds the target has been running, excluding the time stopped by the debugger;
+        /// 0 in recordings made before the timing was added
+        #[serde(default)]
+        clock: u64,
+        /// CPU time of the thread in nanoseconds; 0 if not recorded
+        #[serde(default)]
+        cpu_time: u64,
     },
     FunctionReturn {
         breakpoint_id: u32,
@@ -26,6 +33,26 @@ pub enum Event {
         frame_id: u64,
         function_name: String,
         return_value: RValue,
+        /// Nanoseconds the target has been running, excluding the time stopped by the debugger;
+        /// 0 in recordings made before the timing was added
+        #[serde(default)]
+        clock: u64,
+        /// CPU time of the thread in nanoseconds; 0 if not recorded
+        #[serde(default)]
+        cpu_time: u64,
+    },
+    /// The frame is popped by a panic unwinding through it; it never returns
+    FunctionUnwind {
+        breakpoint_id: u32,
+        thread_id: u64,
+        frame_id: u64,
+        function_name: String,
+        /// Nanoseconds the target has been running, excluding the time stopped by the debugger
+        #[serde(default)]
+        clock: u64,
+        /// CPU time of the thread in nanoseconds; 0 if not recorded
+        #[serde(default)]
+        cpu_time: u64,
     },
 }
 
@@ -35,8 +62,18 @@ pub enum Reason {
     #[default]
     Breakpoint,
     Panic,
+    /// An `async fn` is called and the future is created
     FutureEnter,
+    /// The future of an `async fn` is polled; it carries the `future` address and the `poll` count
+    FuturePoll,
+    /// The future of an `async fn` has completed, i.e. the poll returns `Poll::Ready`
     FutureExit,
+    /// The function has reached `MAX_HITS_PER_FUNCTION` and is no longer traced
+    Truncated,
+    /// A watched value has been written to
+    Watch,
+    /// A panic is caught by `catch_unwind`; the frames it unwound through are popped
+    Catch,
 }
 
 impl Event {
@@ -80,11 +117,15 @@ impl Event {
                 thread_id,
                 stack_pointer,
                 arguments,
+                clock,
+                cpu_time,
                 ..
             } => {
                 *breakpoint_id = u32::MAX;
                 *thread_id = u64::MAX;
                 *stack_pointer = u64::MAX;
+                *clock = 0;
+                *cpu_time = 0;
                 for (_, ref mut argument) in arguments.iter_mut() {
                     argument.redact_addr();
                 }
@@ -93,12 +134,28 @@ impl Event {
                 breakpoint_id,
                 thread_id,
                 return_value,
+                clock,
+                cpu_time,
                 ..
             } => {
                 *breakpoint_id = u32::MAX;
                 *thread_id = u64::MAX;
+                *clock = 0;
+                *cpu_time = 0;
                 return_value.redact_addr();
             }
+            Event::FunctionUnwind {
+                breakpoint_id,
+                thread_id,
+                clock,
+                cpu_time,
+                ..
+            } => {
+                *breakpoint_id = u32::MAX;
+                *thread_id = u64::MAX;
+                *clock = 0;
+                *cpu_time = 0;
+            }
         }
     }
 }
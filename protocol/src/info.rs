@@ -1,8 +1,8 @@
 //! Data structures for Debugger Info
 
-use crate::util::impl_serde_with_str;
+use crate::{source::LineColumn, util::impl_serde_with_str};
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, str::FromStr};
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
 
 pub const FIRE_DBG_FOR_RUST: &str = "FireDBG.for.Rust";
 pub const INFO_STREAM: &str = "info";
@@ -10,6 +10,8 @@ pub const FILE_STREAM: &str = "file";
 pub const BREAKPOINT_STREAM: &str = "breakpoint";
 pub const EVENT_STREAM: &str = "event";
 pub const ALLOCATION_STREAM: &str = "allocation";
+pub const OUTPUT_STREAM: &str = "output";
+pub const PANIC_STREAM: &str = "panic";
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -32,12 +34,61 @@ pub struct DebuggerInfo {
     pub target: String,
     /// Arguments to the executable
     pub arguments: Vec<String>,
+    /// Environment variables set for the executable, on top of the inherited environment
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Environment variables removed from the inherited environment
+    #[serde(default)]
+    pub env_remove: Vec<String>,
+    /// Working directory of the executable; the debugger's if not set
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// Program Exit Info
 pub struct ProgExitInfo {
     pub exit_code: i32,
+    /// The debugger detached from the process, which is still running; `exit_code` is meaningless
+    #[serde(default)]
+    pub detached: bool,
+    /// The signal that terminated the program, e.g. `SIGSEGV`; `exit_code` is meaningless if set
+    #[serde(default)]
+    pub signal: Option<ExitSignal>,
+    /// Why the program exited, as described by the debugger
+    #[serde(default)]
+    pub exit_description: Option<String>,
+    /// Backtrace of every thread at the time the program crashed; empty if it exited normally
+    #[serde(default)]
+    pub backtrace: Vec<ThreadBacktrace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A signal received by the program
+pub struct ExitSignal {
+    /// Platform specific signal number
+    pub number: i32,
+    /// e.g. `SIGABRT`; empty if unknown
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Call stack of a thread, innermost frame first
+pub struct ThreadBacktrace {
+    pub thread_id: u64,
+    pub thread_name: Option<String>,
+    pub frames: Vec<BacktraceFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A frame in the backtrace
+pub struct BacktraceFrame {
+    /// Program counter
+    pub pc: u64,
+    pub function_name: Option<String>,
+    /// Path of the source file; `None` if there is no debug info
+    pub file: Option<String>,
+    pub loc: Option<LineColumn>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -68,7 +119,23 @@ impl InfoMessage {
             InfoMessage::Debugger(ref mut debugger_info) => {
                 debugger_info.redacted();
             }
-            InfoMessage::Exit(_) => (),
+            InfoMessage::Exit(ref mut exit_info) => {
+                exit_info.redacted();
+            }
+        }
+    }
+}
+
+impl ProgExitInfo {
+    pub fn redacted(&mut self) {
+        for thread in self.backtrace.iter_mut() {
+            for frame in thread.frames.iter_mut() {
+                if let Some(file) = &frame.file {
+                    let path = std::path::Path::new(file);
+                    let file_name = path.file_name().expect("file").to_str().expect("str");
+                    frame.file = Some(format!("<redacted>/{file_name}"));
+                }
+            }
         }
     }
 }
@@ -84,6 +151,12 @@ impl DebuggerInfo {
         let path = std::path::Path::new(&self.target);
         let file_name = path.file_name().expect("file").to_str().expect("str");
         self.target = format!("<redacted>/{file_name}");
+
+        if let Some(cwd) = &self.cwd {
+            let path = std::path::Path::new(cwd);
+            let file_name = path.file_name().expect("file").to_str().expect("str");
+            self.cwd = Some(format!("<redacted>/{file_name}"));
+        }
     }
 }
 
@@ -4,7 +4,7 @@
 //! The Protocol defines the different streams and formats of the messages on top of the file format, and thus they have the `.firedbg.ss` extension.
 //! The file format is not tightly-coupled with the stream protocol, as it is possible to stream to/from a different backend, e.g. Redis.
 //!
-//! There are currently 4 streams:
+//! There are currently 7 streams:
 //!
 //! | Stream Key | Format | Description |
 //! |:----------:|:------:|:-----------:|
@@ -13,12 +13,16 @@
 //! | `breakpoint` | Json | Breakpoint: breakpoints created and the source location |
 //! | `event` | Binary | Event: function call, function return, etc |
 //! | `allocation` | Json | Allocation: allocations and deallocations |
+//! | `output` | Json | Output: chunks of the program stdout and stderr, and the input fed to stdin |
+//! | `panic` | Json | Panic: panic message, location and native backtrace |
 pub use indexmap::IndexMap;
 
 pub mod allocation;
 pub mod breakpoint;
 pub mod event;
 pub mod info;
+pub mod output;
+pub mod panic;
 pub mod source;
 mod util;
 pub mod value;
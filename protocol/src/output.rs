@@ -0,0 +1,28 @@
+use crate::util::impl_serde_with_str;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A chunk of the program output, or the input fed to the program
+pub struct Output {
+    pub channel: OutputChannel,
+    /// The thread stopped right after the chunk was written; 0 if the program has exited
+    pub thread_id: u64,
+    /// The innermost recorded frame of that thread; join with `event` on (`thread_id`, `frame_id`); 0 if none
+    pub frame_id: u64,
+    /// Nanoseconds since the program started, on the same clock as `Event`
+    pub clock: u64,
+    /// Invalid UTF-8 sequences are replaced with `U+FFFD`
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+/// Where the output is written to
+pub enum OutputChannel {
+    /// The input read by the program; replay a run by feeding the same input with `--stdin`
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+impl_serde_with_str!(OutputChannel);
@@ -0,0 +1,27 @@
+//! Definition of Panic
+use crate::info::BacktraceFrame;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A panic raised by the program
+pub struct Panic {
+    pub thread_id: u64,
+    /// The innermost recorded frame when the panic is raised; join with the `Reason::Panic` event
+    /// on (`thread_id`, `frame_id`); 0 if none
+    pub frame_id: u64,
+    /// e.g. `index out of bounds: the len is 3 but the index is 5`; `None` if it cannot be read
+    pub message: Option<String>,
+    /// Where the panic is raised; `None` if it cannot be read
+    pub location: Option<PanicLocation>,
+    /// Native backtrace of the panicking thread, innermost frame first; it includes the frames
+    /// of the untraced crates and of the panic machinery
+    pub backtrace: Vec<BacktraceFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Source location of `panic!`, as in `std::panic::Location`
+pub struct PanicLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}